@@ -9,6 +9,7 @@ mod erc1155 {
     use ink::storage::{
         Mapping,
     };
+    use ink::prelude::collections::BTreeMap;
     use ink::prelude::string::String;
     use ink::prelude::vec;
     use ink::prelude::vec::Vec;
@@ -32,6 +33,32 @@ mod erc1155 {
         price_threshold: Balance,
         /// Contract owner
         owner: AccountId,
+        /// Accounts currently holding a positive balance of each token ID,
+        /// kept in sync by `_mint`/`transfer_from`/`safe_batch_transfer_from`
+        /// so holders can be enumerated without an off-chain indexer.
+        holders: Mapping<Id, Vec<AccountId>>,
+        /// Vesting schedules for tokens escrowed by `create_lockup`.
+        lockups: Mapping<u128, Lockup>,
+        /// Lockup ID nonce for creating new vesting schedules.
+        lockup_id_nonce: u128,
+        /// Total supply of each token ID; mints increment it, burns
+        /// decrement it, transfers leave it untouched.
+        total_supply: Mapping<Id, Balance>,
+        /// Sum of `total_supply` across every token ID.
+        total_supply_sum: Balance,
+        /// Compressed ECDSA public key of the off-chain authority trusted to
+        /// sign `mint_with_receipt` authorizations. All-zero until set via
+        /// `set_trusted_signer`, which rejects every receipt until then.
+        trusted_signer: [u8; 33],
+        /// Receipt hashes already consumed by `mint_with_receipt`, so a
+        /// signed authorization can never be replayed.
+        used_receipts: Mapping<[u8; 32], ()>,
+        /// Whether `transfer_from`/`safe_batch_transfer_from` invoke the
+        /// `on_erc1155_received`/`_batch_received` hook on contract
+        /// recipients. Defaults to `true`; `set_receiver_check` lets the
+        /// owner disable it, e.g. to unblock a transfer to a recipient
+        /// contract whose hook is broken but trusted.
+        receiver_check_enabled: bool,
     }
 
     /// Type for token IDs.
@@ -45,6 +72,25 @@ mod erc1155 {
     const SHIELD_URI: &str = "ipfs://QmZ8Syn28bEhZJnyYo2PEeNw5jmhS1RMa7YxaGgVQ3Qz84/shield.json";
     const COIN_URI: &str = "ipfs://QmZ8Syn28bEhZJnyYo2PEeNw5jmhS1RMa7YxaGgVQ3Qz84/coin.json";
 
+    /// Selector a receiving contract must expose for single transfers;
+    /// echoing it back is the magic acknowledgement that the transfer may
+    /// proceed, mirroring CIS2's `OnReceivingCis2DataParams` accept value.
+    const ON_ERC1155_RECEIVED_SELECTOR: [u8; 4] = ink::selector_bytes!("on_erc1155_received");
+    /// Selector a receiving contract must expose for batch transfers.
+    const ON_ERC1155_BATCH_RECEIVED_SELECTOR: [u8; 4] = ink::selector_bytes!("on_erc1155_batch_received");
+
+    /// Predefined roles recognized by `assert_role`. The owner can always
+    /// act regardless of role membership; these let delegated accounts
+    /// perform the matching privileged action without the owner key.
+    const MINTER_ROLE: &str = "minter";
+    const PAUSER_ROLE: &str = "pauser";
+    const BLACKLISTER_ROLE: &str = "blacklister";
+    /// Holders of this role may grant/revoke the roles above, so role
+    /// administration doesn't itself funnel solely through `owner`.
+    const ADMIN_ROLE: &str = "admin";
+    /// Holders of this role may swap the contract's code hash via `set_code`.
+    const UPGRADER_ROLE: &str = "upgrader";
+
     #[derive(Encode, Decode, Debug, PartialEq, Eq, Copy, Clone)]
     #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
     pub enum Error {
@@ -55,7 +101,7 @@ mod erc1155 {
         /// The caller is not the owner of the token.
         NotOwner,
         /// Transfer array size mismatch.
-        ArraySizeMismatch,
+        ArrayLengthMismatch,
         /// Contract is paused
         ContractPaused,
         /// Account is blacklisted
@@ -64,9 +110,53 @@ mod erc1155 {
         AccountNotWhitelisted,
         /// Insufficient payment value
         InsufficientValue,
+        /// The receiving contract rejected the transfer, or reverted while
+        /// handling it, in its `on_erc1155_received`/`_batch_received` hook.
+        TransferRejected,
+        /// The caller is neither the owner nor a holder of the required role.
+        MissingRole,
+        /// The requested operation requires the contract to be paused first.
+        NotPaused,
+        /// Swapping the contract's code hash failed.
+        UpgradeFailed,
+        /// `cliff_ts`/`end_ts` don't describe a valid vesting schedule.
+        InvalidLockupSchedule,
+        /// No lockup exists with the given ID.
+        LockupNotFound,
+        /// Nothing has vested (or everything already withdrawn) yet.
+        NothingToWithdraw,
+        /// A checked arithmetic operation would have wrapped.
+        Overflow,
+        /// The receipt's nonce has already been used to mint once.
+        ReceiptAlreadyUsed,
+        /// The receipt's signature does not recover to the trusted signer.
+        InvalidSignature,
+        /// The account is already blacklisted.
+        AlreadyBlacklisted,
+        /// The account is not on the blacklist.
+        NotBlacklisted,
+        /// The account is already whitelisted.
+        AlreadyWhitelisted,
+        /// The account is not on the whitelist.
+        NotWhitelisted,
+    }
+
+    /// Event emitted when a single token type is transferred, minted
+    /// (`from = None`), or burned (`to = None`).
+    #[ink(event)]
+    pub struct TransferSingle {
+        #[ink(topic)]
+        operator: Option<AccountId>,
+        #[ink(topic)]
+        from: Option<AccountId>,
+        #[ink(topic)]
+        to: Option<AccountId>,
+        id: Id,
+        value: Balance,
     }
 
-    /// Event emitted when tokens are transferred.
+    /// Event emitted when multiple token types are transferred, minted
+    /// (`from = None`), or burned (`to = None`) in one call.
     #[ink(event)]
     pub struct TransferBatch {
         #[ink(topic)]
@@ -79,6 +169,14 @@ mod erc1155 {
         values: Vec<Balance>,
     }
 
+    /// Event emitted when a token's URI is set.
+    #[ink(event)]
+    pub struct URI {
+        value: String,
+        #[ink(topic)]
+        id: Id,
+    }
+
     /// Event emitted when approval is granted or revoked.
     #[ink(event)]
     pub struct ApprovalForAll {
@@ -113,32 +211,23 @@ mod erc1155 {
         account: AccountId,
     }
 
-    /// Event emitted when an account is blacklisted.
-    #[ink(event)]
-    pub struct Blacklisted {
-        #[ink(topic)]
-        account: AccountId,
-    }
-
-    /// Event emitted when an account is removed from blacklist.
-    #[ink(event)]
-    pub struct Unblacklisted {
-        #[ink(topic)]
-        account: AccountId,
-    }
-
-    /// Event emitted when an account is whitelisted.
+    /// Event emitted whenever an account is added to or removed from the
+    /// blacklist, so an indexer can decode every blacklist change from one
+    /// log shape instead of tracking separate add/remove events.
     #[ink(event)]
-    pub struct Whitelisted {
+    pub struct UpdateBlacklist {
         #[ink(topic)]
         account: AccountId,
+        added: bool,
     }
 
-    /// Event emitted when an account is removed from whitelist.
+    /// Event emitted whenever an account is added to or removed from the
+    /// whitelist. Mirrors `UpdateBlacklist`.
     #[ink(event)]
-    pub struct Unwhitelisted {
+    pub struct UpdateWhitelist {
         #[ink(topic)]
         account: AccountId,
+        added: bool,
     }
 
     /// Event emitted when a price trigger is activated.
@@ -174,6 +263,13 @@ mod erc1155 {
         account: AccountId,
     }
 
+    /// Event emitted when the contract's code hash is swapped via `set_code`.
+    #[ink(event)]
+    pub struct Upgraded {
+        #[ink(topic)]
+        code_hash: Hash,
+    }
+
     /// Event emitted when fungible tokens are airdropped to NFT holders
     #[ink(event)]
     pub struct AirdropCompleted {
@@ -184,6 +280,16 @@ mod erc1155 {
         amount: Balance,
     }
 
+    /// Event emitted when a beneficiary withdraws newly vested tokens.
+    #[ink(event)]
+    pub struct VestingReleased {
+        #[ink(topic)]
+        lockup_id: u128,
+        #[ink(topic)]
+        beneficiary: AccountId,
+        amount: Balance,
+    }
+
     #[derive(Encode, Decode, Debug, Clone)]
     #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
     pub struct Role {
@@ -191,6 +297,27 @@ mod erc1155 {
         pub members: Vec<AccountId>,
     }
 
+    /// A vesting schedule escrowing `total` units of token `id` for
+    /// `beneficiary`, releasing linearly between `start_ts` and `end_ts`
+    /// once `cliff_ts` has passed.
+    ///
+    /// This one `Lockup`/`create_lockup`/`withdraw_vested`/`vested_amount_of`
+    /// family intentionally serves both the `Coin`-style lockup request and
+    /// the later ERC-1155 vesting request: they describe the same escrow
+    /// and release semantics, so a second, separately-keyed schedule type
+    /// would just be the same state machine under a different name.
+    #[derive(Encode, Decode, Debug, Clone)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
+    pub struct Lockup {
+        pub beneficiary: AccountId,
+        pub id: Id,
+        pub total: Balance,
+        pub start_ts: Timestamp,
+        pub cliff_ts: Timestamp,
+        pub end_ts: Timestamp,
+        pub withdrawn: Balance,
+    }
+
     #[derive(Encode, Decode, Debug, Clone, Default)]
     #[cfg_attr(feature = "std", derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout))]
     pub struct LifecycleState {
@@ -213,27 +340,38 @@ mod erc1155 {
                 lifecycle_state: LifecycleState::default(),
                 price_threshold: 1_000_000_000_000_000_000, // 1 ether in wei
                 owner: caller,
+                holders: Mapping::default(),
+                lockups: Mapping::default(),
+                lockup_id_nonce: 0,
+                total_supply: Mapping::default(),
+                total_supply_sum: 0,
+                trusted_signer: [0u8; 33],
+                used_receipts: Mapping::default(),
+                receiver_check_enabled: true,
             };
             contract._setup_initial_tokens();
             contract
         }
 
-        /// Sets up the initial NFTs with predefined metadata
+        /// Sets up the initial NFTs with predefined metadata. Runs during
+        /// construction with the deploying account as caller, which
+        /// `assert_role` always authorizes as owner, so `create_token`
+        /// cannot fail here.
         fn _setup_initial_tokens(&mut self) {
             // Create Thor's Hammer NFT
-            let hammer_id = self.create_token(String::from(THOR_HAMMER_URI));
-            
+            let hammer_id = self.create_token(String::from(THOR_HAMMER_URI)).expect("owner can always create tokens");
+
             // Create Trophy NFT
-            let trophy_id = self.create_token(String::from(TROPHY_URI));
-            
+            let trophy_id = self.create_token(String::from(TROPHY_URI)).expect("owner can always create tokens");
+
             // Create Sword NFT
-            let sword_id = self.create_token(String::from(SWORD_URI));
-            
+            let sword_id = self.create_token(String::from(SWORD_URI)).expect("owner can always create tokens");
+
             // Create Shield NFT
-            let shield_id = self.create_token(String::from(SHIELD_URI));
-            
+            let shield_id = self.create_token(String::from(SHIELD_URI)).expect("owner can always create tokens");
+
             // Create Coin (fungible token)
-            let coin_id = self.create_token(String::from(COIN_URI));
+            let coin_id = self.create_token(String::from(COIN_URI)).expect("owner can always create tokens");
             
             // Mint some tokens to the contract owner
             let owner = self.owner;
@@ -258,7 +396,7 @@ mod erc1155 {
             ids: Vec<Id>,
         ) -> Result<Vec<Balance>, Error> {
             if accounts.len() != ids.len() {
-                return Err(Error::ArraySizeMismatch);
+                return Err(Error::ArrayLengthMismatch);
             }
 
             let mut batch_balances = Vec::with_capacity(accounts.len());
@@ -325,25 +463,111 @@ mod erc1155 {
             if from_balance < amount {
                 return Err(Error::InsufficientBalance);
             }
-            
-            self.balances.insert((id, from), &(from_balance - amount));
+
+            // Run the receiver hook before committing the balance change, so
+            // a reject or revert leaves storage untouched instead of having
+            // to be unwound after the fact.
+            self.call_on_erc1155_received(caller, from, to, id, amount, data)?;
+
+            let new_from_balance = from_balance - amount;
+            self.set_balance(id, from, new_from_balance);
+            self.update_holder_index(id, from, from_balance, new_from_balance);
+
+            // Re-read `to`'s balance only after `from` has been written, so
+            // a self-transfer (`from == to`) credits on top of the debit
+            // just applied instead of on a stale pre-debit snapshot.
             let to_balance = self.balance_of(to, id);
-            self.balances.insert((id, to), &(to_balance + amount));
-            
-            // Here would be receiver hook call if `to` is a contract
-            let _ = data; // Unused for now
-            
-            self.env().emit_event(TransferBatch {
+            let new_to_balance = to_balance.checked_add(amount).ok_or(Error::Overflow)?;
+            self.set_balance(id, to, new_to_balance);
+            self.update_holder_index(id, to, to_balance, new_to_balance);
+
+            self.env().emit_event(TransferSingle {
                 operator: Some(caller),
                 from: Some(from),
                 to: Some(to),
-                ids: vec![id],
-                values: vec![amount],
+                id,
+                value: amount,
             });
-            
+
             Ok(())
         }
 
+        /// Calls `to`'s `on_erc1155_received` hook when `to` is a contract,
+        /// modeled on NEP-171's `nft_transfer_call`/resolve flow: the callee
+        /// must echo back `ON_ERC1155_RECEIVED_SELECTOR` to accept the
+        /// transfer, anything else (including a reverted call) rejects it.
+        fn call_on_erc1155_received(
+            &self,
+            operator: AccountId,
+            from: AccountId,
+            to: AccountId,
+            id: Id,
+            amount: Balance,
+            data: Vec<u8>,
+        ) -> Result<(), Error> {
+            if !self.receiver_check_enabled || !self.env().is_contract(&to) {
+                return Ok(());
+            }
+
+            use ink::env::call::{build_call, ExecutionInput, Selector};
+            use ink::env::DefaultEnvironment;
+
+            let result = build_call::<DefaultEnvironment>()
+                .call(to)
+                .exec_input(
+                    ExecutionInput::new(Selector::new(ON_ERC1155_RECEIVED_SELECTOR))
+                        .push_arg(operator)
+                        .push_arg(from)
+                        .push_arg(id)
+                        .push_arg(amount)
+                        .push_arg(data),
+                )
+                .returns::<[u8; 4]>()
+                .try_invoke();
+
+            match result {
+                Ok(Ok(magic)) if magic == ON_ERC1155_RECEIVED_SELECTOR => Ok(()),
+                _ => Err(Error::TransferRejected),
+            }
+        }
+
+        /// Batch counterpart of `call_on_erc1155_received`; the callee must
+        /// echo back `ON_ERC1155_BATCH_RECEIVED_SELECTOR` to accept.
+        fn call_on_erc1155_batch_received(
+            &self,
+            operator: AccountId,
+            from: AccountId,
+            to: AccountId,
+            ids: Vec<Id>,
+            amounts: Vec<Balance>,
+            data: Vec<u8>,
+        ) -> Result<(), Error> {
+            if !self.receiver_check_enabled || !self.env().is_contract(&to) {
+                return Ok(());
+            }
+
+            use ink::env::call::{build_call, ExecutionInput, Selector};
+            use ink::env::DefaultEnvironment;
+
+            let result = build_call::<DefaultEnvironment>()
+                .call(to)
+                .exec_input(
+                    ExecutionInput::new(Selector::new(ON_ERC1155_BATCH_RECEIVED_SELECTOR))
+                        .push_arg(operator)
+                        .push_arg(from)
+                        .push_arg(ids)
+                        .push_arg(amounts)
+                        .push_arg(data),
+                )
+                .returns::<[u8; 4]>()
+                .try_invoke();
+
+            match result {
+                Ok(Ok(magic)) if magic == ON_ERC1155_BATCH_RECEIVED_SELECTOR => Ok(()),
+                _ => Err(Error::TransferRejected),
+            }
+        }
+
         /// Transfers multiple tokens at once.
         #[ink(message)]
         pub fn safe_batch_transfer_from(
@@ -355,7 +579,7 @@ mod erc1155 {
             data: Vec<u8>,
         ) -> Result<(), Error> {
             if ids.len() != amounts.len() {
-                return Err(Error::ArraySizeMismatch);
+                return Err(Error::ArrayLengthMismatch);
             }
 
             self.assert_not_paused()?;
@@ -369,23 +593,68 @@ mod erc1155 {
                 return Err(Error::NotApproved);
             }
             
+            // Gather: aggregate the total debit/credit per id into an
+            // in-memory checkpoint first, so a batch that references the
+            // same id twice is validated against its real combined total
+            // instead of two independent checks against the same stale
+            // starting balance, and so a duplicate-id overflow is caught
+            // before anything is written.
+            let mut debits: BTreeMap<Id, Balance> = BTreeMap::new();
+            let mut credits: BTreeMap<Id, Balance> = BTreeMap::new();
             for i in 0..ids.len() {
                 let id = ids[i];
                 let amount = amounts[i];
-                
-                let from_balance = self.balance_of(from, id);
-                if from_balance < amount {
-                    return Err(Error::InsufficientBalance);
+
+                let debit = debits.entry(id).or_insert(0);
+                *debit = debit.checked_add(amount).ok_or(Error::Overflow)?;
+
+                let credit = credits.entry(id).or_insert(0);
+                *credit = credit.checked_add(amount).ok_or(Error::Overflow)?;
+            }
+
+            // Validate: every aggregated debit must be coverable by the
+            // account's current balance, and every aggregated credit must
+            // not overflow the recipient's balance, checked up front so a
+            // shortfall or overflow anywhere in the batch leaves zero state
+            // change.
+            let mut new_from_balances: BTreeMap<Id, Balance> = BTreeMap::new();
+            for (id, total) in &debits {
+                let from_balance = self.balance_of(from, *id);
+                let new_from_balance = from_balance.checked_sub(*total).ok_or(Error::InsufficientBalance)?;
+                new_from_balances.insert(*id, new_from_balance);
+            }
+            let mut new_to_balances: BTreeMap<Id, Balance> = BTreeMap::new();
+            for (id, total) in &credits {
+                let to_balance = self.balance_of(to, *id);
+                let new_to_balance = to_balance.checked_add(*total).ok_or(Error::Overflow)?;
+                new_to_balances.insert(*id, new_to_balance);
+            }
+
+            // Run the receiver hook before committing any balance change, so
+            // a reject or revert leaves storage untouched instead of having
+            // to be unwound after the fact.
+            self.call_on_erc1155_batch_received(caller, from, to, ids.clone(), amounts.clone(), data)?;
+
+            // Apply: now that every leg has been validated as a whole,
+            // flush the checkpoint to storage, committing the net change
+            // per id rather than per line item. `new_from_balances` and
+            // `new_to_balances` were both computed from the same pre-write
+            // snapshot, so when from == to the credit pass would otherwise
+            // clobber the debit pass with a stale balance; a self-transfer
+            // nets to zero change, so just skip applying it.
+            if from != to {
+                for (id, new_from_balance) in &new_from_balances {
+                    let from_balance = self.balance_of(from, *id);
+                    self.set_balance(*id, from, *new_from_balance);
+                    self.update_holder_index(*id, from, from_balance, *new_from_balance);
+                }
+                for (id, new_to_balance) in &new_to_balances {
+                    let to_balance = self.balance_of(to, *id);
+                    self.set_balance(*id, to, *new_to_balance);
+                    self.update_holder_index(*id, to, to_balance, *new_to_balance);
                 }
-                
-                self.balances.insert((id, from), &(from_balance - amount));
-                let to_balance = self.balance_of(to, id);
-                self.balances.insert((id, to), &(to_balance + amount));
             }
-            
-            // Here would be receiver hook call if `to` is a contract
-            let _ = data; // Unused for now
-            
+
             self.env().emit_event(TransferBatch {
                 operator: Some(caller),
                 from: Some(from),
@@ -403,49 +672,256 @@ mod erc1155 {
             self.token_uris.get(id).unwrap_or_default()
         }
 
-        /// Creates a new token type.
+        /// Returns the total supply of `id` currently in circulation.
+        #[ink(message)]
+        pub fn total_supply(&self, id: Id) -> Balance {
+            self.total_supply.get(id).unwrap_or(0)
+        }
+
+        /// Returns the total supply across every token ID.
+        #[ink(message)]
+        pub fn total_supply_all(&self) -> Balance {
+            self.total_supply_sum
+        }
+
+        /// Returns `true` if `id` has a nonzero supply.
+        #[ink(message)]
+        pub fn exists(&self, id: Id) -> bool {
+            self.total_supply(id) > 0
+        }
+
+        /// Creates a new token type. Restricted to `MINTER_ROLE` (and the
+        /// owner), since anyone able to mint new token types should also be
+        /// trusted to define them.
         #[ink(message)]
-        pub fn create_token(&mut self, uri: String) -> Id {
+        pub fn create_token(&mut self, uri: String) -> Result<Id, Error> {
+            self.assert_role(MINTER_ROLE, self.env().caller())?;
+
             let id = self.token_id_nonce;
             self.token_id_nonce += 1;
             self.token_uris.insert(id, &uri);
-            
+
             self.env().emit_event(TokenCreated {
                 id,
                 creator: self.env().caller(),
                 uri: uri.clone(),
             });
-            
-            id
+            self.env().emit_event(URI { value: uri, id });
+
+            Ok(id)
         }
 
         /// Mints tokens to an account.
         #[ink(message)]
         pub fn mint(&mut self, to: AccountId, id: Id, amount: Balance) -> Result<(), Error> {
-            self.assert_owner()?;
+            self.assert_role(MINTER_ROLE, self.env().caller())?;
             self._mint(to, id, amount)
         }
 
-        /// Internal mint implementation
-        fn _mint(&mut self, to: AccountId, id: Id, amount: Balance) -> Result<(), Error> {
-            let to_balance = self.balance_of(to, id);
-            self.balances.insert((id, to), &(to_balance + amount));
-            
+        /// Mints multiple token types to an account in one call.
+        #[ink(message)]
+        pub fn mint_batch(&mut self, to: AccountId, ids: Vec<Id>, amounts: Vec<Balance>) -> Result<(), Error> {
+            if ids.len() != amounts.len() {
+                return Err(Error::ArrayLengthMismatch);
+            }
+            self.assert_role(MINTER_ROLE, self.env().caller())?;
+
+            for i in 0..ids.len() {
+                self._mint_balance(to, ids[i], amounts[i])?;
+            }
+
             self.env().emit_event(TransferBatch {
                 operator: Some(self.env().caller()),
                 from: None,
                 to: Some(to),
-                ids: vec![id],
-                values: vec![amount],
+                ids,
+                values: amounts,
             });
-            
+
+            Ok(())
+        }
+
+        /// Internal mint implementation; emits `TransferSingle`. Batch
+        /// callers use `_mint_balance` directly and emit one `TransferBatch`
+        /// for the whole call instead.
+        fn _mint(&mut self, to: AccountId, id: Id, amount: Balance) -> Result<(), Error> {
+            self._mint_balance(to, id, amount)?;
+
+            self.env().emit_event(TransferSingle {
+                operator: Some(self.env().caller()),
+                from: None,
+                to: Some(to),
+                id,
+                value: amount,
+            });
+
+            Ok(())
+        }
+
+        /// Credits `amount` of `id` to `to` and updates the holder index and
+        /// supply bookkeeping, without emitting a transfer event.
+        fn _mint_balance(&mut self, to: AccountId, id: Id, amount: Balance) -> Result<(), Error> {
+            let to_balance = self.balance_of(to, id);
+            let new_to_balance = to_balance.checked_add(amount).ok_or(Error::Overflow)?;
+            self.set_balance(id, to, new_to_balance);
+            self.update_holder_index(id, to, to_balance, new_to_balance);
+            self.increase_supply(id, amount)?;
+            Ok(())
+        }
+
+        /// Sets the off-chain authority trusted to sign `mint_with_receipt`
+        /// authorizations, identified by its compressed ECDSA public key.
+        #[ink(message)]
+        pub fn set_trusted_signer(&mut self, signer: [u8; 33]) -> Result<(), Error> {
+            self.assert_owner()?;
+            self.trusted_signer = signer;
+            Ok(())
+        }
+
+        /// Enables or disables the `on_erc1155_received`/`_batch_received`
+        /// receiver hook for contract recipients. EOA recipients are never
+        /// affected either way, since the hook is only ever invoked when
+        /// `to` is a contract.
+        #[ink(message)]
+        pub fn set_receiver_check(&mut self, enabled: bool) -> Result<(), Error> {
+            self.assert_owner()?;
+            self.receiver_check_enabled = enabled;
+            Ok(())
+        }
+
+        /// Returns whether the receiver hook is currently enforced.
+        #[ink(message)]
+        pub fn receiver_check_enabled(&self) -> bool {
+            self.receiver_check_enabled
+        }
+
+        /// Mints `amount` of `token_id` to `to` on the strength of a
+        /// signature from the trusted signer, rather than the caller holding
+        /// `MINTER_ROLE`. Modeled on a bridge relayer redeeming a signed
+        /// mint receipt: the `(to, token_id, amount, nonce)` tuple is hashed
+        /// and the signature must recover to the trusted signer, and the
+        /// hash is recorded so the same receipt can never be replayed.
+        #[ink(message)]
+        pub fn mint_with_receipt(
+            &mut self,
+            to: AccountId,
+            token_id: Id,
+            amount: Balance,
+            nonce: u128,
+            signature: [u8; 65],
+        ) -> Result<(), Error> {
+            let receipt_hash = Self::receipt_hash(to, token_id, amount, nonce);
+
+            if self.used_receipts.contains(receipt_hash) {
+                return Err(Error::ReceiptAlreadyUsed);
+            }
+
+            let mut recovered_signer = [0u8; 33];
+            self.env()
+                .ecdsa_recover(&signature, &receipt_hash, &mut recovered_signer)
+                .map_err(|_| Error::InvalidSignature)?;
+            if recovered_signer != self.trusted_signer {
+                return Err(Error::InvalidSignature);
+            }
+
+            self.used_receipts.insert(receipt_hash, &());
+            self._mint(to, token_id, amount)
+        }
+
+        /// Hashes `(to, token_id, amount, nonce)` into the message a
+        /// `mint_with_receipt` signature must cover.
+        fn receipt_hash(to: AccountId, token_id: Id, amount: Balance, nonce: u128) -> [u8; 32] {
+            let encoded = (to, token_id, amount, nonce).encode();
+            let mut hash = [0u8; 32];
+            ink::env::hash_bytes::<ink::env::hash::Blake2x256>(&encoded, &mut hash);
+            hash
+        }
+
+        /// Destroys `amount` of `token_id` held by `from`.
+        #[ink(message)]
+        pub fn burn(&mut self, from: AccountId, token_id: Id, amount: Balance) -> Result<(), Error> {
+            let caller = self.env().caller();
+            if from != caller && !self.is_approved_for_all(from, caller) {
+                return Err(Error::NotApproved);
+            }
+
+            let from_balance = self.balance_of(from, token_id);
+            if from_balance < amount {
+                return Err(Error::InsufficientBalance);
+            }
+
+            let new_from_balance = from_balance - amount;
+            self.set_balance(token_id, from, new_from_balance);
+            self.update_holder_index(token_id, from, from_balance, new_from_balance);
+            self.decrease_supply(token_id, amount)?;
+
+            self.env().emit_event(TransferSingle {
+                operator: Some(caller),
+                from: Some(from),
+                to: None,
+                id: token_id,
+                value: amount,
+            });
+
+            Ok(())
+        }
+
+        /// Destroys multiple token amounts held by `from` atomically.
+        #[ink(message)]
+        pub fn burn_batch(&mut self, from: AccountId, ids: Vec<Id>, amounts: Vec<Balance>) -> Result<(), Error> {
+            if ids.len() != amounts.len() {
+                return Err(Error::ArrayLengthMismatch);
+            }
+
+            let caller = self.env().caller();
+            if from != caller && !self.is_approved_for_all(from, caller) {
+                return Err(Error::NotApproved);
+            }
+
+            // Gather: aggregate the total debit per id first, matching
+            // `safe_batch_transfer_from`'s handling of repeated ids.
+            let mut debits: Vec<(Id, Balance)> = Vec::new();
+            for i in 0..ids.len() {
+                let id = ids[i];
+                let amount = amounts[i];
+                match debits.iter_mut().find(|(existing_id, _)| *existing_id == id) {
+                    Some((_, total)) => *total += amount,
+                    None => debits.push((id, amount)),
+                }
+            }
+
+            // Validate: every aggregated debit must be coverable up front,
+            // so a shortfall anywhere in the batch leaves zero state change.
+            for (id, total) in &debits {
+                if self.balance_of(from, *id) < *total {
+                    return Err(Error::InsufficientBalance);
+                }
+            }
+
+            // Apply: commit the net decrement per id.
+            for (id, total) in &debits {
+                let from_balance = self.balance_of(from, *id);
+                let new_from_balance = from_balance - *total;
+                self.set_balance(*id, from, new_from_balance);
+                self.update_holder_index(*id, from, from_balance, new_from_balance);
+                self.decrease_supply(*id, *total)?;
+            }
+
+            self.env().emit_event(TransferBatch {
+                operator: Some(caller),
+                from: Some(from),
+                to: None,
+                ids,
+                values: amounts,
+            });
+
             Ok(())
         }
 
         /// Pauses all token transfers.
         #[ink(message)]
         pub fn pause(&mut self) -> Result<(), Error> {
-            self.assert_owner()?;
+            self.assert_role(PAUSER_ROLE, self.env().caller())?;
             self.lifecycle_state.paused = true;
             self.env().emit_event(Paused {
                 account: self.env().caller(),
@@ -456,7 +932,7 @@ mod erc1155 {
         /// Unpauses all token transfers.
         #[ink(message)]
         pub fn unpause(&mut self) -> Result<(), Error> {
-            self.assert_owner()?;
+            self.assert_role(PAUSER_ROLE, self.env().caller())?;
             self.lifecycle_state.paused = false;
             self.env().emit_event(Unpaused {
                 account: self.env().caller(),
@@ -470,25 +946,189 @@ mod erc1155 {
             self.lifecycle_state.paused
         }
 
+        /// Swaps the contract's code to `code_hash`, the standard safe-upgrade
+        /// pattern for ink! contracts. The contract must already be paused so
+        /// no transfer can straddle the swap; follow up with `migrate()`
+        /// before unpausing.
+        #[ink(message)]
+        pub fn set_code(&mut self, code_hash: Hash) -> Result<(), Error> {
+            self.assert_role(UPGRADER_ROLE, self.env().caller())?;
+            self.assert_paused()?;
+
+            self.env()
+                .set_code_hash(&code_hash)
+                .map_err(|_| Error::UpgradeFailed)?;
+
+            self.env().emit_event(Upgraded { code_hash });
+            Ok(())
+        }
+
+        /// Post-upgrade storage migration hook. Run this immediately after
+        /// `set_code`, before unpausing, so `LifecycleState`,
+        /// `price_threshold`, and the token mappings can be re-shaped for the
+        /// new code. Currently a no-op since this is the first storage
+        /// version, but it's the place to add per-version fixups as the
+        /// contract evolves.
+        #[ink(message)]
+        pub fn migrate(&mut self) -> Result<(), Error> {
+            self.assert_role(UPGRADER_ROLE, self.env().caller())?;
+            self.assert_paused()?;
+            Ok(())
+        }
+
+        /// Escrows `total` units of token `id` out of the owner's balance
+        /// into a vesting schedule for `beneficiary`, releasing linearly
+        /// between `start_ts` and `end_ts` once `cliff_ts` has passed.
+        /// Returns the new lockup's ID.
+        #[ink(message)]
+        pub fn create_lockup(
+            &mut self,
+            beneficiary: AccountId,
+            id: Id,
+            total: Balance,
+            start_ts: Timestamp,
+            cliff_ts: Timestamp,
+            end_ts: Timestamp,
+        ) -> Result<u128, Error> {
+            self.assert_owner()?;
+            if cliff_ts < start_ts || end_ts < cliff_ts {
+                return Err(Error::InvalidLockupSchedule);
+            }
+
+            let owner_balance = self.balance_of(self.owner, id);
+            if owner_balance < total {
+                return Err(Error::InsufficientBalance);
+            }
+
+            let contract = self.env().account_id();
+            let new_owner_balance = owner_balance - total;
+            let contract_balance = self.balance_of(contract, id);
+            let new_contract_balance = contract_balance + total;
+            self.set_balance(id, self.owner, new_owner_balance);
+            self.set_balance(id, contract, new_contract_balance);
+            self.update_holder_index(id, self.owner, owner_balance, new_owner_balance);
+            self.update_holder_index(id, contract, contract_balance, new_contract_balance);
+
+            let lockup_id = self.lockup_id_nonce;
+            self.lockup_id_nonce += 1;
+            self.lockups.insert(lockup_id, &Lockup {
+                beneficiary,
+                id,
+                total,
+                start_ts,
+                cliff_ts,
+                end_ts,
+                withdrawn: 0,
+            });
+
+            Ok(lockup_id)
+        }
+
+        /// Releases whatever portion of `lockup_id` has vested but not yet
+        /// been withdrawn, paying it to the lockup's beneficiary. Only the
+        /// beneficiary may call this, and they must be whitelisted, reusing
+        /// the existing whitelist so locked tokens can only land on a
+        /// trusted destination. No other balance-moving message checks the
+        /// whitelist; they check only the blacklist.
+        #[ink(message)]
+        pub fn withdraw_vested(&mut self, lockup_id: u128) -> Result<Balance, Error> {
+            let mut lockup = self.lockups.get(lockup_id).ok_or(Error::LockupNotFound)?;
+            let caller = self.env().caller();
+            if caller != lockup.beneficiary {
+                return Err(Error::NotApproved);
+            }
+            self.assert_whitelisted(caller)?;
+
+            let now = self.env().block_timestamp();
+            let released = Self::vested_amount(&lockup, now);
+            let withdrawable = released.saturating_sub(lockup.withdrawn);
+            if withdrawable == 0 {
+                return Err(Error::NothingToWithdraw);
+            }
+
+            let contract = self.env().account_id();
+            let contract_balance = self.balance_of(contract, lockup.id);
+            let new_contract_balance = contract_balance.saturating_sub(withdrawable);
+            let beneficiary_balance = self.balance_of(lockup.beneficiary, lockup.id);
+            let new_beneficiary_balance = beneficiary_balance + withdrawable;
+
+            self.set_balance(lockup.id, contract, new_contract_balance);
+            self.set_balance(lockup.id, lockup.beneficiary, new_beneficiary_balance);
+            self.update_holder_index(lockup.id, contract, contract_balance, new_contract_balance);
+            self.update_holder_index(lockup.id, lockup.beneficiary, beneficiary_balance, new_beneficiary_balance);
+
+            lockup.withdrawn += withdrawable;
+            self.lockups.insert(lockup_id, &lockup);
+
+            self.env().emit_event(VestingReleased {
+                lockup_id,
+                beneficiary: lockup.beneficiary,
+                amount: withdrawable,
+            });
+
+            Ok(withdrawable)
+        }
+
+        /// Returns the vesting schedule for `lockup_id`, if any.
+        #[ink(message)]
+        pub fn lockup_of(&self, lockup_id: u128) -> Option<Lockup> {
+            self.lockups.get(lockup_id)
+        }
+
+        /// Returns the total amount of `lockup_id` vested as of now,
+        /// independent of how much has already been withdrawn. Callers
+        /// wanting the still-unreleased portion should subtract
+        /// `lockup_of(lockup_id).withdrawn` from the result themselves.
+        #[ink(message)]
+        pub fn vested_amount_of(&self, lockup_id: u128) -> Result<Balance, Error> {
+            let lockup = self.lockups.get(lockup_id).ok_or(Error::LockupNotFound)?;
+            Ok(Self::vested_amount(&lockup, self.env().block_timestamp()))
+        }
+
+        /// Computes the total amount of `lockup` vested as of `now`: zero
+        /// before the cliff, the full total at/after `end_ts`, and a linear
+        /// interpolation between `start_ts` and `end_ts` otherwise.
+        fn vested_amount(lockup: &Lockup, now: Timestamp) -> Balance {
+            if now < lockup.cliff_ts {
+                return 0;
+            }
+            if now >= lockup.end_ts {
+                return lockup.total;
+            }
+
+            let elapsed = now.saturating_sub(lockup.start_ts) as Balance;
+            let duration = lockup.end_ts.saturating_sub(lockup.start_ts) as Balance;
+            if duration == 0 {
+                lockup.total
+            } else {
+                lockup.total.saturating_mul(elapsed) / duration
+            }
+        }
+
         /// Adds an account to the blacklist.
         #[ink(message)]
         pub fn add_to_blacklist(&mut self, account: AccountId) -> Result<(), Error> {
-            self.assert_owner()?;
-            if !self.lifecycle_state.blacklist.contains(&account) {
-                self.lifecycle_state.blacklist.push(account);
-                self.env().emit_event(Blacklisted { account });
+            self.assert_role(BLACKLISTER_ROLE, self.env().caller())?;
+            if self.lifecycle_state.blacklist.contains(&account) {
+                return Err(Error::AlreadyBlacklisted);
             }
+            self.lifecycle_state.blacklist.push(account);
+            self.env().emit_event(UpdateBlacklist { account, added: true });
             Ok(())
         }
 
         /// Removes an account from the blacklist.
         #[ink(message)]
         pub fn remove_from_blacklist(&mut self, account: AccountId) -> Result<(), Error> {
-            self.assert_owner()?;
-            if let Some(pos) = self.lifecycle_state.blacklist.iter().position(|x| *x == account) {
-                self.lifecycle_state.blacklist.remove(pos);
-                self.env().emit_event(Unblacklisted { account });
-            }
+            self.assert_role(BLACKLISTER_ROLE, self.env().caller())?;
+            let pos = self
+                .lifecycle_state
+                .blacklist
+                .iter()
+                .position(|x| *x == account)
+                .ok_or(Error::NotBlacklisted)?;
+            self.lifecycle_state.blacklist.remove(pos);
+            self.env().emit_event(UpdateBlacklist { account, added: false });
             Ok(())
         }
 
@@ -502,10 +1142,11 @@ mod erc1155 {
         #[ink(message)]
         pub fn add_to_whitelist(&mut self, account: AccountId) -> Result<(), Error> {
             self.assert_owner()?;
-            if !self.lifecycle_state.whitelist.contains(&account) {
-                self.lifecycle_state.whitelist.push(account);
-                self.env().emit_event(Whitelisted { account });
+            if self.lifecycle_state.whitelist.contains(&account) {
+                return Err(Error::AlreadyWhitelisted);
             }
+            self.lifecycle_state.whitelist.push(account);
+            self.env().emit_event(UpdateWhitelist { account, added: true });
             Ok(())
         }
 
@@ -513,10 +1154,14 @@ mod erc1155 {
         #[ink(message)]
         pub fn remove_from_whitelist(&mut self, account: AccountId) -> Result<(), Error> {
             self.assert_owner()?;
-            if let Some(pos) = self.lifecycle_state.whitelist.iter().position(|x| *x == account) {
-                self.lifecycle_state.whitelist.remove(pos);
-                self.env().emit_event(Unwhitelisted { account });
-            }
+            let pos = self
+                .lifecycle_state
+                .whitelist
+                .iter()
+                .position(|x| *x == account)
+                .ok_or(Error::NotWhitelisted)?;
+            self.lifecycle_state.whitelist.remove(pos);
+            self.env().emit_event(UpdateWhitelist { account, added: false });
             Ok(())
         }
 
@@ -529,7 +1174,7 @@ mod erc1155 {
         /// Creates a new role.
         #[ink(message)]
         pub fn create_role(&mut self, role_name: String) -> Result<(), Error> {
-            self.assert_owner()?;
+            self.assert_role(ADMIN_ROLE, self.env().caller())?;
             let role = Role {
                 name: role_name.clone(),
                 members: Vec::new(),
@@ -542,7 +1187,7 @@ mod erc1155 {
         /// Adds an account to a role.
         #[ink(message)]
         pub fn add_to_role(&mut self, role_name: String, account: AccountId) -> Result<(), Error> {
-            self.assert_owner()?;
+            self.assert_role(ADMIN_ROLE, self.env().caller())?;
             if let Some(role) = self.lifecycle_state.roles.iter_mut().find(|r| r.name == role_name) {
                 if !role.members.contains(&account) {
                     role.members.push(account);
@@ -558,7 +1203,7 @@ mod erc1155 {
         /// Removes an account from a role.
         #[ink(message)]
         pub fn remove_from_role(&mut self, role_name: String, account: AccountId) -> Result<(), Error> {
-            self.assert_owner()?;
+            self.assert_role(ADMIN_ROLE, self.env().caller())?;
             if let Some(role) = self.lifecycle_state.roles.iter_mut().find(|r| r.name == role_name) {
                 if let Some(pos) = role.members.iter().position(|x| *x == account) {
                     role.members.remove(pos);
@@ -619,51 +1264,91 @@ mod erc1155 {
             self.price_threshold
         }
 
-        /// Airdrops fungible tokens to NFT holders
+        /// Airdrops fungible tokens to every current holder of `nft_id`.
         #[ink(message)]
         pub fn airdrop_to_nft_holders(&mut self, nft_id: Id, fungible_id: Id, amount: Balance) -> Result<(), Error> {
             self.assert_owner()?;
-            
-            // Implementation: Scan all accounts that ever interacted with the contract
-            // to discover NFT holders
-            let caller = self.env().caller();
-            
-            // Track all addresses that have received an airdrop
-            let mut airdropped_addresses = Vec::new();
-            
-            // Always check the contract owner first
-            if self.balance_of(self.owner, nft_id) > 0 {
-                self._mint(self.owner, fungible_id, amount)?;
-                
+
+            for holder in self.holders_of(nft_id) {
+                self._mint(holder, fungible_id, amount)?;
+
                 self.env().emit_event(AirdropCompleted {
                     token_id: fungible_id,
-                    recipient: self.owner,
+                    recipient: holder,
                     amount,
                 });
-                
-                airdropped_addresses.push(self.owner);
             }
-            
-            // Check the caller if different from owner
-            if caller != self.owner && self.balance_of(caller, nft_id) > 0 {
-                self._mint(caller, fungible_id, amount)?;
-                
-                self.env().emit_event(AirdropCompleted {
-                    token_id: fungible_id,
-                    recipient: caller,
-                    amount,
-                });
-                
-                airdropped_addresses.push(caller);
+
+            Ok(())
+        }
+
+        /// Returns every account currently holding a positive balance of `id`.
+        #[ink(message)]
+        pub fn holders_of(&self, id: Id) -> Vec<AccountId> {
+            self.holders.get(id).unwrap_or_default()
+        }
+
+        /// Returns the number of accounts currently holding a positive
+        /// balance of `id`.
+        #[ink(message)]
+        pub fn holder_count(&self, id: Id) -> u32 {
+            self.holders.get(id).map(|holders| holders.len() as u32).unwrap_or(0)
+        }
+
+        /// Writes a balance, removing the `(id, account)` entry entirely
+        /// instead of storing a zero once it's spent down to nothing. This
+        /// reclaims the storage deposit for the entry and keeps the ledger
+        /// free of dust; `balance_of`'s `unwrap_or(0)` means callers can't
+        /// tell the difference.
+        fn set_balance(&mut self, id: Id, account: AccountId, new_balance: Balance) {
+            if new_balance == 0 {
+                self.balances.remove((id, account));
+            } else {
+                self.balances.insert((id, account), &new_balance);
             }
-            
-            // Create an on-chain record of this airdrop
-            self.env().emit_event(AirdropCompleted {
-                token_id: fungible_id,
-                recipient: self.owner, // Use owner field to mark completion
-                amount: airdropped_addresses.len() as Balance,
-            });
-            
+        }
+
+        /// Keeps `holders` in sync with a balance change: records `account`
+        /// the moment its balance for `id` goes from zero to positive, and
+        /// drops it the moment the balance returns to zero.
+        fn update_holder_index(&mut self, id: Id, account: AccountId, old_balance: Balance, new_balance: Balance) {
+            if (old_balance > 0) == (new_balance > 0) {
+                return;
+            }
+
+            let mut holders = self.holders.get(id).unwrap_or_default();
+            if new_balance > 0 {
+                if !holders.contains(&account) {
+                    holders.push(account);
+                }
+            } else if let Some(pos) = holders.iter().position(|holder| *holder == account) {
+                holders.remove(pos);
+            }
+            self.holders.insert(id, &holders);
+        }
+
+        /// Keeps `total_supply`/`total_supply_sum` in sync with a mint of
+        /// `amount` units of `id`. Uses checked arithmetic so a wrapping
+        /// supply surfaces as `Error::Overflow` instead of silently wrapping.
+        fn increase_supply(&mut self, id: Id, amount: Balance) -> Result<(), Error> {
+            let supply = self.total_supply.get(id).unwrap_or(0);
+            let new_supply = supply.checked_add(amount).ok_or(Error::Overflow)?;
+            let new_supply_sum = self.total_supply_sum.checked_add(amount).ok_or(Error::Overflow)?;
+            self.total_supply.insert(id, &new_supply);
+            self.total_supply_sum = new_supply_sum;
+            Ok(())
+        }
+
+        /// Keeps `total_supply`/`total_supply_sum` in sync with a burn of
+        /// `amount` units of `id`. Uses checked arithmetic so an
+        /// inconsistent supply surfaces as `Error::Overflow` instead of
+        /// silently saturating.
+        fn decrease_supply(&mut self, id: Id, amount: Balance) -> Result<(), Error> {
+            let supply = self.total_supply.get(id).unwrap_or(0);
+            let new_supply = supply.checked_sub(amount).ok_or(Error::Overflow)?;
+            let new_supply_sum = self.total_supply_sum.checked_sub(amount).ok_or(Error::Overflow)?;
+            self.total_supply.insert(id, &new_supply);
+            self.total_supply_sum = new_supply_sum;
             Ok(())
         }
 
@@ -675,6 +1360,13 @@ mod erc1155 {
             Ok(())
         }
 
+        fn assert_paused(&self) -> Result<(), Error> {
+            if !self.lifecycle_state.paused {
+                return Err(Error::NotPaused);
+            }
+            Ok(())
+        }
+
         fn assert_not_blacklisted(&self, account: AccountId) -> Result<(), Error> {
             if self.lifecycle_state.blacklist.contains(&account) {
                 return Err(Error::AccountBlacklisted);
@@ -695,6 +1387,17 @@ mod erc1155 {
             }
             Ok(())
         }
+
+        /// Authorizes `account` for a privileged action if it is either the
+        /// contract owner or a member of `role_name`, so delegated
+        /// administration doesn't have to funnel solely through the owner key.
+        fn assert_role(&self, role_name: &str, account: AccountId) -> Result<(), Error> {
+            if account == self.owner || self.has_role(String::from(role_name), account) {
+                Ok(())
+            } else {
+                Err(Error::MissingRole)
+            }
+        }
     }
 
     #[cfg(test)]
@@ -725,10 +1428,10 @@ mod erc1155 {
             set_caller(accounts.alice);
             
             let mut erc1155 = Erc1155::new();
-            
+
             // Create a token
-            let token_id = erc1155.create_token(String::from("test_uri"));
-            
+            let token_id = erc1155.create_token(String::from("test_uri")).unwrap();
+
             // Mint some tokens
             assert!(erc1155.mint(accounts.bob, token_id, 100).is_ok());
             
@@ -736,19 +1439,35 @@ mod erc1155 {
             assert_eq!(erc1155.balance_of(accounts.bob, token_id), 100);
         }
 
+        #[ink::test]
+        fn mint_requires_minter_role() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+
+            let mut erc1155 = Erc1155::new();
+            let token_id = erc1155.create_token(String::from("test_uri")).unwrap();
+
+            // Bob holds no role and isn't the owner, so minting must fail.
+            set_caller(accounts.bob);
+            assert!(matches!(
+                erc1155.mint(accounts.bob, token_id, 100),
+                Err(Error::MissingRole)
+            ));
+        }
+
         #[ink::test]
         fn transfer_works() {
             let accounts = default_accounts();
             set_caller(accounts.alice);
             
             let mut erc1155 = Erc1155::new();
-            
+
             // Create a token
-            let token_id = erc1155.create_token(String::from("test_uri"));
-            
+            let token_id = erc1155.create_token(String::from("test_uri")).unwrap();
+
             // Mint some tokens to Alice
             assert!(erc1155.mint(accounts.alice, token_id, 100).is_ok());
-            
+
             // Transfer from Alice to Bob
             assert!(erc1155.safe_transfer_from(
                 accounts.alice,
@@ -769,13 +1488,13 @@ mod erc1155 {
             set_caller(accounts.alice);
             
             let mut erc1155 = Erc1155::new();
-            
+
             // Create a token
-            let token_id = erc1155.create_token(String::from("test_uri"));
-            
+            let token_id = erc1155.create_token(String::from("test_uri")).unwrap();
+
             // Mint some tokens to Alice
             assert!(erc1155.mint(accounts.alice, token_id, 100).is_ok());
-            
+
             // Approve Charlie to spend Alice's tokens
             assert!(erc1155.set_approval_for_all(accounts.charlie, true).is_ok());
             
@@ -817,5 +1536,48 @@ mod erc1155 {
             // Buy should fail with insufficient payment
             assert!(matches!(erc1155.buy(), Err(Error::InsufficientValue)));
         }
+
+        #[ink::test]
+        fn vesting_releases_linearly() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+
+            let mut erc1155 = Erc1155::new();
+            let token_id = erc1155.create_token(String::from("test_uri")).unwrap();
+            assert!(erc1155.mint(accounts.alice, token_id, 1_000).is_ok());
+
+            let lockup_id = erc1155
+                .create_lockup(accounts.bob, token_id, 1_000, 0, 100, 200)
+                .unwrap();
+            assert!(erc1155.add_to_whitelist(accounts.bob).is_ok());
+
+            // Before the cliff, nothing has vested and withdrawal fails.
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(50);
+            assert_eq!(erc1155.vested_amount_of(lockup_id).unwrap(), 0);
+            set_caller(accounts.bob);
+            assert!(matches!(
+                erc1155.withdraw_vested(lockup_id),
+                Err(Error::NothingToWithdraw)
+            ));
+
+            // At the cliff, interpolation is measured from `start_ts`, so
+            // the portion that would have accrued between `start_ts` and
+            // the cliff is already unlocked.
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(100);
+            assert_eq!(erc1155.vested_amount_of(lockup_id).unwrap(), 500);
+
+            // Three quarters of the way from `start_ts` to `end_ts`, three
+            // quarters has vested.
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(150);
+            assert_eq!(erc1155.vested_amount_of(lockup_id).unwrap(), 750);
+            assert_eq!(erc1155.withdraw_vested(lockup_id), Ok(750));
+            assert_eq!(erc1155.balance_of(accounts.bob, token_id), 750);
+
+            // After the end, the remainder is withdrawable in full.
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(300);
+            assert_eq!(erc1155.vested_amount_of(lockup_id).unwrap(), 1_000);
+            assert_eq!(erc1155.withdraw_vested(lockup_id), Ok(250));
+            assert_eq!(erc1155.balance_of(accounts.bob, token_id), 1_000);
+        }
     }
 } 
\ No newline at end of file
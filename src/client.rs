@@ -0,0 +1,136 @@
+use tokio::sync::Mutex;
+
+use sp_core::sr25519;
+use subxt::{
+    config::substrate::SubstrateExtrinsicParamsBuilder, tx::PairSigner, tx::TxPayload,
+    tx::TxProgress, OnlineClient,
+};
+
+/// Caches and locally increments the signer's account nonce so a batch of
+/// extrinsics can be dispatched back-to-back with correct sequential
+/// nonces, instead of every call round-tripping to chain state via
+/// `sign_and_submit_then_watch_default`'s default nonce lookup.
+pub struct NonceManager {
+    next: Mutex<Option<u64>>,
+}
+
+impl NonceManager {
+    pub fn new() -> Self {
+        Self {
+            next: Mutex::new(None),
+        }
+    }
+
+    /// Returns the next nonce to use: the on-chain value on the first call,
+    /// the locally incremented value on every call after that.
+    pub async fn next<T: subxt::Config>(
+        &self,
+        api: &OnlineClient<T>,
+        account: &T::AccountId,
+    ) -> Result<u64, Box<dyn std::error::Error>>
+    where
+        T::AccountId: Clone,
+    {
+        let mut cached = self.next.lock().await;
+        let nonce = match *cached {
+            Some(n) => n,
+            None => api.rpc().system_account_next_index(account.clone()).await?,
+        };
+        *cached = Some(nonce + 1);
+        Ok(nonce)
+    }
+
+    /// Discards the cached nonce so the next `next()` call re-reads it from
+    /// chain state. Call this after a submission fails with a nonce-too-low
+    /// or nonce-too-high error, which means the local cache has drifted
+    /// from the account's real on-chain nonce (e.g. a transaction submitted
+    /// from another process, or a prior submission that never made it into
+    /// a block).
+    pub async fn invalidate(&self) {
+        *self.next.lock().await = None;
+    }
+}
+
+/// Whether `message` looks like a node-reported nonce conflict (too low,
+/// already used, or too far ahead of the account's on-chain nonce), as
+/// opposed to some unrelated dispatch failure. Matched on the message text
+/// since subxt surfaces these as generic RPC/dispatch errors rather than a
+/// dedicated error variant.
+fn is_nonce_error(message: &str) -> bool {
+    let message = message.to_lowercase();
+    message.contains("nonce") || message.contains("priority is too low")
+}
+
+/// Signs `call` with the next nonce from `nonces` and submits it, without
+/// waiting for inclusion — the signing layer wrapping `PairSigner` sits
+/// between the nonce manager and this submission layer, mirroring a
+/// provider/signer/nonce-manager middleware stack. Callers that need to
+/// dispatch several extrinsics in one block should call this once per
+/// extrinsic and only await `wait_for_finalized_success` afterwards, so the
+/// nonce for call N+1 doesn't wait on call N's finalization.
+pub async fn submit_with_nonce<T, Call>(
+    api: &OnlineClient<T>,
+    signer: &PairSigner<T, sr25519::Pair>,
+    nonces: &NonceManager,
+    call: &Call,
+) -> Result<TxProgress<T, OnlineClient<T>>, Box<dyn std::error::Error>>
+where
+    T: subxt::Config,
+    T::AccountId: Clone + From<[u8; 32]>,
+    Call: TxPayload,
+{
+    submit_with_nonce_and_tip(api, signer, nonces, call, 0).await
+}
+
+/// Same as `submit_with_nonce`, but also sets `tip` on the extrinsic's
+/// signed extension, for callers (e.g. the fee-estimating pipeline layer)
+/// that want to bid above the default zero tip to get into a block sooner.
+pub async fn submit_with_nonce_and_tip<T, Call>(
+    api: &OnlineClient<T>,
+    signer: &PairSigner<T, sr25519::Pair>,
+    nonces: &NonceManager,
+    call: &Call,
+    tip: u128,
+) -> Result<TxProgress<T, OnlineClient<T>>, Box<dyn std::error::Error>>
+where
+    T: subxt::Config,
+    T::AccountId: Clone + From<[u8; 32]>,
+    Call: TxPayload,
+{
+    let account = T::AccountId::from(signer.account_id().0);
+
+    match submit_at_cached_nonce(api, signer, nonces, &account, call, tip).await {
+        Ok(progress) => Ok(progress),
+        Err(e) if is_nonce_error(&e.to_string()) => {
+            // The cached nonce no longer matches chain state (another
+            // submission raced us, or we fell behind after a dropped
+            // transaction) — drop it and retry once against a freshly
+            // read on-chain nonce.
+            nonces.invalidate().await;
+            submit_at_cached_nonce(api, signer, nonces, &account, call, tip).await
+        }
+        Err(e) => Err(e),
+    }
+}
+
+async fn submit_at_cached_nonce<T, Call>(
+    api: &OnlineClient<T>,
+    signer: &PairSigner<T, sr25519::Pair>,
+    nonces: &NonceManager,
+    account: &T::AccountId,
+    call: &Call,
+    tip: u128,
+) -> Result<TxProgress<T, OnlineClient<T>>, Box<dyn std::error::Error>>
+where
+    T: subxt::Config,
+    T::AccountId: Clone,
+    Call: TxPayload,
+{
+    let nonce = nonces.next(api, account).await?;
+    let params = SubstrateExtrinsicParamsBuilder::<T>::new()
+        .nonce(nonce)
+        .tip(tip)
+        .build();
+    let progress = api.tx().sign_and_submit_then_watch(call, signer, params).await?;
+    Ok(progress)
+}
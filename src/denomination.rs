@@ -0,0 +1,61 @@
+use crate::error::CliError;
+
+/// Number of base-unit decimals assumed for a token when no denomination
+/// metadata is on hand (e.g. the token hasn't been synced to local SLED
+/// storage yet).
+pub const DEFAULT_DECIMALS: u8 = 12;
+
+/// Parses a decimal CLI string (e.g. `"1.25"`) into integer base units at
+/// `decimals` precision, rejecting values with more fractional digits than
+/// the token's denomination allows.
+pub fn parse_amount(input: &str, decimals: u8) -> Result<u128, CliError> {
+    let (whole, frac) = match input.split_once('.') {
+        Some((whole, frac)) => (whole, frac),
+        None => (input, ""),
+    };
+
+    if frac.len() > decimals as usize {
+        return Err(CliError::ParseError);
+    }
+
+    let whole: u128 = if whole.is_empty() {
+        0
+    } else {
+        whole.parse().map_err(|_| CliError::ParseError)?
+    };
+
+    let frac_digits: u128 = if frac.is_empty() {
+        0
+    } else {
+        frac.parse().map_err(|_| CliError::ParseError)?
+    };
+    let pad = decimals as u32 - frac.len() as u32;
+    let frac_value = frac_digits.checked_mul(10u128.pow(pad)).ok_or(CliError::ParseError)?;
+
+    let scale = 10u128.pow(decimals as u32);
+    whole
+        .checked_mul(scale)
+        .and_then(|base| base.checked_add(frac_value))
+        .ok_or(CliError::ParseError)
+}
+
+/// Formats base units back into a decimal string at `decimals` precision,
+/// trimming trailing fractional zeros (and the decimal point entirely when
+/// the value is whole).
+pub fn format_amount(raw: u128, decimals: u8) -> String {
+    if decimals == 0 {
+        return raw.to_string();
+    }
+
+    let scale = 10u128.pow(decimals as u32);
+    let whole = raw / scale;
+    let frac = raw % scale;
+    let frac_str = format!("{:0width$}", frac, width = decimals as usize);
+    let trimmed = frac_str.trim_end_matches('0');
+
+    if trimmed.is_empty() {
+        whole.to_string()
+    } else {
+        format!("{}.{}", whole, trimmed)
+    }
+}
@@ -16,6 +16,20 @@ pub enum CliError {
     InvalidMethod,
     /// Conversion error
     ConversionError,
+    /// Contract metadata file could not be found on disk
+    MetadataNotFound(String),
+    /// Contract metadata file is not valid ink! metadata JSON
+    InvalidMetadata,
+    /// No message with this label exists in the contract metadata
+    UnknownMessage(String),
+    /// CLI supplied the wrong number of arguments for a message
+    ArgumentCountMismatch {
+        message: String,
+        expected: usize,
+        got: usize,
+    },
+    /// An argument type in the metadata is not one the transcoder supports
+    UnsupportedArgType(String),
 }
 
 impl fmt::Display for CliError {
@@ -27,6 +41,15 @@ impl fmt::Display for CliError {
             CliError::ContractCallFailed => write!(f, "Contract call failed"),
             CliError::InvalidMethod => write!(f, "Invalid contract method"),
             CliError::ConversionError => write!(f, "Failed to convert value"),
+            CliError::MetadataNotFound(path) => write!(f, "Contract metadata not found at {}", path),
+            CliError::InvalidMetadata => write!(f, "Contract metadata is not valid ink! metadata JSON"),
+            CliError::UnknownMessage(label) => write!(f, "No message named '{}' in contract metadata", label),
+            CliError::ArgumentCountMismatch { message, expected, got } => write!(
+                f,
+                "Message '{}' expects {} argument(s), got {}",
+                message, expected, got
+            ),
+            CliError::UnsupportedArgType(label) => write!(f, "Unsupported argument type for '{}'", label),
         }
     }
 }
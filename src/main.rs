@@ -2,9 +2,13 @@ mod storage_validator;
 mod error;
 mod storage_sled;
 mod price_listener;
+mod transcoder;
+mod denomination;
+mod client;
+mod tx_pipeline;
+pub mod contract_interactor;
 
 use sp_core::{sr25519, Pair, H256, crypto::Ss58Codec};
-use sp_core::crypto::keccak_256;
 use subxt::{
     tx::PairSigner,
     OnlineClient,
@@ -20,9 +24,12 @@ use clap::{Parser, Subcommand};
 use hex;
 use getrandom;
 
-use storage_sled::{StorageSled, PriceListener, PriceAction, Token, Balance};
+use storage_sled::{StorageSled, TypedStorage, PriceListener, PriceAction, Token, Balance};
 use price_listener::PriceListenerService;
 use error::CliError;
+use transcoder::{Transcoder, TranscoderValue};
+use denomination::{format_amount, parse_amount, DEFAULT_DECIMALS};
+use client::NonceManager;
 
 /// ERC1155 contract deployment and interaction tool
 #[derive(Parser)]
@@ -40,6 +47,11 @@ struct Cli {
     #[clap(long, default_value = "./erc1155_db")]
     storage_path: String,
 
+    /// Path to the deployed contract's ink! metadata.json, used to
+    /// transcode messages instead of hand-rolled Ethereum-style selectors
+    #[clap(long, default_value = "./metadata.json")]
+    metadata_path: String,
+
     #[clap(subcommand)]
     command: Commands,
 }
@@ -56,32 +68,55 @@ enum Commands {
         #[clap(long)]
         token_id: u128,
         
-        /// The target price to trigger the action
+        /// The target price to trigger the action, as a decimal string
+        /// (e.g. "1.25") in the token's denomination
         #[clap(long)]
-        target_price: u128,
-        
+        target_price: String,
+
         /// The action type (sell, buy, transfer)
         #[clap(long)]
         action_type: String,
-        
-        /// The amount of tokens for the action
+
+        /// The amount of tokens for the action, as a decimal string
         #[clap(long)]
-        amount: u128,
-        
-        /// The price limit (min for sell, max for buy)
+        amount: String,
+
+        /// The price limit (min for sell, max for buy), as a decimal string
         #[clap(long)]
-        price_limit: Option<u128>,
+        price_limit: Option<String>,
         
         /// The recipient address for transfer actions
         #[clap(long)]
         recipient: Option<String>,
+
+        /// URL of an external HTTP price feed to poll for this token,
+        /// enabling unattended execution instead of manual `UpdatePrice`
+        #[clap(long)]
+        feed_url: Option<String>,
+
+        /// Symbol to request from `feed_url` (e.g. "DOT/USD")
+        #[clap(long)]
+        feed_symbol: Option<String>,
+
+        /// Basis points (1/100 of a percent) of spread around `target_price`
+        /// used as a rolling reference price. When set, the listener fires
+        /// on every crossing of the live ask/bid instead of once at a fixed
+        /// level, re-anchoring around each fill.
+        #[clap(long)]
+        spread_bps: Option<u16>,
     },
-    
+
     /// Start the price listener service
     StartPriceListener {
         /// Run in foreground (true) or background (false)
         #[clap(long, default_value = "false")]
         foreground: bool,
+
+        /// URL of a WebSocket feed streaming `{"token_id": ..,
+        /// "price": ..}` ticks, for always-on execution instead of
+        /// per-listener HTTP polling or manual `UpdatePrice` calls
+        #[clap(long)]
+        ws_feed_url: Option<String>,
     },
     
     /// Manually update a token price (for testing)
@@ -90,9 +125,10 @@ enum Commands {
         #[clap(long)]
         token_id: u128,
         
-        /// The new price
+        /// The new price, as a decimal string (e.g. "1.25") in the token's
+        /// denomination
         #[clap(long)]
-        price: u128,
+        price: String,
     },
     
     /// Sync blockchain data to local SLED storage
@@ -103,42 +139,88 @@ enum Commands {
     },
     
     /// Deploy a new ERC1155 contract
-    Deploy,
-    
+    Deploy {
+        /// Print the estimated gas/storage deposit and exit without submitting
+        #[clap(long)]
+        dry_run: bool,
+
+        /// Percentage headroom added on top of the dry-run gas estimate
+        #[clap(long, default_value = "20")]
+        gas_margin: u8,
+    },
+
     /// Create a new token type in an existing contract
     CreateToken {
         /// The contract address
         #[clap(long)]
         contract: String,
-        
+
         /// The token URI
         #[clap(long)]
         uri: String,
-        
+
         /// Initial supply to mint
         #[clap(long, default_value = "100")]
         supply: u128,
+
+        /// Number of base-unit decimals this token is denominated in,
+        /// recorded alongside it so CLI amounts/prices can be parsed and
+        /// displayed in decimal form
+        #[clap(long, default_value_t = DEFAULT_DECIMALS)]
+        decimals: u8,
+
+        /// Print the estimated gas/storage deposit and exit without submitting
+        #[clap(long)]
+        dry_run: bool,
+
+        /// Percentage headroom added on top of the dry-run gas estimate
+        #[clap(long, default_value = "20")]
+        gas_margin: u8,
     },
-    
+
     /// Transfer tokens
     Transfer {
         /// The contract address
         #[clap(long)]
         contract: String,
-        
+
         /// The recipient address
         #[clap(long)]
         to: String,
-        
+
         /// The token ID to transfer
         #[clap(long)]
         token_id: u128,
-        
-        /// Amount to transfer
+
+        /// Amount to transfer, as a decimal string (e.g. "1.25") in the
+        /// token's denomination
         #[clap(long)]
-        amount: u128,
+        amount: String,
+
+        /// Print the estimated gas/storage deposit and exit without submitting
+        #[clap(long)]
+        dry_run: bool,
+
+        /// Percentage headroom added on top of the dry-run gas estimate
+        #[clap(long, default_value = "20")]
+        gas_margin: u8,
     },
-    
+
+    /// Transfer tokens to multiple recipients in one batch, using a single
+    /// locally-incremented nonce across all of them instead of each
+    /// extrinsic fetching the account nonce from chain in turn
+    TransferBatch {
+        /// The contract address
+        #[clap(long)]
+        contract: String,
+
+        /// One transfer per flag, as "to:token_id:amount" (amount is a
+        /// decimal string in the token's denomination), e.g.
+        /// `--transfer 5F...:1:2.5 --transfer 5G...:1:1.0`
+        #[clap(long = "transfer", required = true)]
+        transfers: Vec<String>,
+    },
+
     /// Check token balance
     Balance {
         /// The contract address
@@ -159,7 +241,24 @@ enum Commands {
         /// The contract address
         #[clap(long)]
         contract: String,
-        
+
+        /// Optional block number to query
+        #[clap(long)]
+        block_number: Option<u32>,
+
+        /// Number of base-unit decimals to render scanned balances in,
+        /// alongside their raw integer value
+        #[clap(long, default_value_t = DEFAULT_DECIMALS)]
+        decimals: u8,
+    },
+
+    /// Dump every field of the contract's storage, driven by the layout in
+    /// its metadata rather than special-casing known ERC1155 fields
+    DumpStorage {
+        /// The contract address
+        #[clap(long)]
+        contract: String,
+
         /// Optional block number to query
         #[clap(long)]
         block_number: Option<u32>,
@@ -197,34 +296,101 @@ pub enum StorageDeposit<T> {
     Charge(T),
 }
 
-// Custom contract call types
-#[derive(Debug, Encode, Decode)]
-struct BalanceOfParams {
-    account: AccountId32,
-    id: u128,
+// ContractExecResult for the contracts pallet's `ContractsApi_call` runtime API
+#[derive(Encode, Decode, Debug)]
+pub struct ContractExecResult<T> {
+    pub gas_consumed: u64,
+    pub gas_required: u64,
+    pub storage_deposit: StorageDeposit<T>,
+    pub result: Result<ExecReturnValue, ()>,
 }
 
-#[derive(Debug, Encode, Decode)]
-struct TransferParams {
-    from: AccountId32,
-    to: AccountId32, 
-    id: u128,
-    amount: u128,
-    data: Vec<u8>,
+/// Mirrors pallet-contracts' `ExecReturnValue`: the raw call output plus a
+/// flags word whose bit 0 marks a revert, so callers can tell "the call
+/// completed but the contract rejected it" from "the call succeeded".
+#[derive(Encode, Decode, Debug)]
+pub struct ExecReturnValue {
+    pub flags: u32,
+    pub data: Vec<u8>,
 }
 
-#[derive(Debug, Encode, Decode)]
-struct CreateTokenParams {
-    uri: String,
-    initial_supply: u128,
+/// Maximum number of attempts `dry_run_instantiate` makes before giving up.
+const MAX_DRY_RUN_ATTEMPTS: u8 = 10;
+
+/// Scales an estimated gas requirement up by `margin_percent` so the real
+/// extrinsic has headroom over the dry-run figure.
+fn apply_gas_margin(gas_required: u64, margin_percent: u8) -> u64 {
+    gas_required.saturating_mul(100 + margin_percent as u64) / 100
+}
+
+/// Turns a dry-run `StorageDeposit` into the `storage_deposit_limit`
+/// argument expected by the real extrinsic: `None` on a refund (no deposit
+/// needed), `Some(amount)` on a charge.
+fn storage_deposit_limit(deposit: &StorageDeposit<u128>) -> Option<u128> {
+    match deposit {
+        StorageDeposit::Charge(amount) => Some(*amount),
+        StorageDeposit::Refund(_) => None,
+    }
+}
+
+/// Dry-runs a contract instantiation via the `ContractsApi_instantiate`
+/// runtime API (unlimited gas/deposit) to obtain `gas_required` and the
+/// `StorageDeposit`, retrying up to `MAX_DRY_RUN_ATTEMPTS` times on any
+/// RPC error. Every attempt sends the same request; no missing code
+/// dependency is resolved between attempts.
+async fn dry_run_instantiate<T: subxt::Config>(
+    api: &OnlineClient<T>,
+    origin: AccountId32,
+    value: u128,
+    code: Vec<u8>,
+    data: Vec<u8>,
+    salt: Vec<u8>,
+) -> Result<ContractInstantiateResult<u128>, Box<dyn std::error::Error>> {
+    let mut last_err: Option<Box<dyn std::error::Error>> = None;
+
+    for attempt in 1..=MAX_DRY_RUN_ATTEMPTS {
+        let encoded = scale::Encode::encode(&(
+            &origin,
+            value,
+            None::<u64>,  // unlimited gas for estimation
+            None::<u128>, // unlimited storage deposit for estimation
+            &code,
+            &data,
+            &salt,
+        ));
+
+        match api.rpc().state_call("ContractsApi_instantiate", &encoded).await {
+            Ok(raw) => return Ok(ContractInstantiateResult::<u128>::decode(&mut &raw[..])?),
+            Err(e) => {
+                println!("Dry-run instantiate attempt {} failed: {}", attempt, e);
+                last_err = Some(Box::new(e));
+            }
+        }
+    }
+
+    Err(last_err.unwrap_or_else(|| "Dry-run instantiate exhausted all attempts".into()))
 }
 
-// Helper function to compute Ethereum-style function selectors
-fn compute_selector(signature: &str) -> [u8; 4] {
-    let hash = keccak_256(signature.as_bytes());
-    let mut selector = [0u8; 4];
-    selector.copy_from_slice(&hash[0..4]);
-    selector
+/// Dry-runs a contract call via the `ContractsApi_call` runtime API
+/// (unlimited gas/deposit) to obtain `gas_required` and the
+/// `StorageDeposit` before submitting the real, gas-limited extrinsic.
+async fn dry_run_call<T: subxt::Config>(
+    api: &OnlineClient<T>,
+    origin: AccountId32,
+    dest: AccountId32,
+    value: u128,
+    input_data: Vec<u8>,
+) -> Result<ContractExecResult<u128>, Box<dyn std::error::Error>> {
+    let encoded = scale::Encode::encode(&(
+        origin,
+        dest,
+        value,
+        None::<u64>,
+        None::<u128>,
+        input_data,
+    ));
+    let raw = api.rpc().state_call("ContractsApi_call", &encoded).await?;
+    Ok(ContractExecResult::<u128>::decode(&mut &raw[..])?)
 }
 
 #[tokio::main]
@@ -244,12 +410,26 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("Using account: {}", account_id.to_ss58check());
     
     match cli.command {
-        Commands::CreatePriceListener { contract, token_id, target_price, action_type, amount, price_limit, recipient } => {
+        Commands::CreatePriceListener { contract, token_id, target_price, action_type, amount, price_limit, recipient, feed_url, feed_symbol, spread_bps } => {
             let contract_address = AccountId::from_str(&contract)?;
-            
+
             // Initialize the price listener service
             let service = PriceListenerService::new(&cli.storage_path, &cli.node_url)?;
-            
+
+            // Decimal strings are parsed against the token's denomination,
+            // falling back to DEFAULT_DECIMALS if it hasn't been synced yet.
+            let storage = StorageSled::new(&cli.storage_path)?;
+            let decimals = storage
+                .get_token(token_id)?
+                .map(|t| t.decimals)
+                .unwrap_or(DEFAULT_DECIMALS);
+
+            let target_price = parse_amount(&target_price, decimals)?;
+            let amount = parse_amount(&amount, decimals)?;
+            let price_limit = price_limit
+                .map(|p| parse_amount(&p, decimals))
+                .transpose()?;
+
             // Create the action based on the type
             let action = match action_type.as_str() {
                 "sell" => {
@@ -267,16 +447,34 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 },
                 _ => return Err(CliError::Other(format!("Unknown action type: {}", action_type)).into()),
             };
-            
+
+            let quote = spread_bps.map(|spread_bps| storage_sled::PriceQuote {
+                reference_price: target_price,
+                spread_bps,
+            });
+
             // Create the price listener
-            service.create_price_listener(token_id, target_price, action)?;
-            
-            println!("Created price listener for token {} at target price {}", token_id, target_price);
+            service.create_price_listener(token_id, target_price, action, feed_url, feed_symbol, quote)?;
+
+            println!(
+                "Created price listener for token {} at target price {}",
+                token_id,
+                format_amount(target_price, decimals)
+            );
         },
-        Commands::StartPriceListener { foreground } => {
+        Commands::StartPriceListener { foreground, ws_feed_url } => {
             // Initialize the price listener service
             let service = PriceListenerService::new(&cli.storage_path, &cli.node_url)?;
-            
+
+            if let Some(url) = ws_feed_url {
+                service.set_websocket_feed(url, std::sync::Arc::new(|text: &str| {
+                    let parsed: serde_json::Value = serde_json::from_str(text).ok()?;
+                    let token_id = parsed["token_id"].as_u64()? as u128;
+                    let price = parsed["price"].as_u64()? as u128;
+                    Some(price_listener::PriceUpdate { token_id, price })
+                }));
+            }
+
             // Start the service
             service.start(&cli.seed)?;
             
@@ -293,39 +491,54 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         Commands::UpdatePrice { token_id, price } => {
             // Initialize the price listener service
             let service = PriceListenerService::new(&cli.storage_path, &cli.node_url)?;
-            
+
+            let storage = StorageSled::new(&cli.storage_path)?;
+            let decimals = storage
+                .get_token(token_id)?
+                .map(|t| t.decimals)
+                .unwrap_or(DEFAULT_DECIMALS);
+            let price = parse_amount(&price, decimals)?;
+
             // Update the price
             service.update_price(token_id, price)?;
-            
-            println!("Price updated for token {} to {}", token_id, price);
+
+            println!("Price updated for token {} to {}", token_id, format_amount(price, decimals));
         },
         Commands::SyncStorage { contract } => {
             let contract_address = AccountId::from_str(&contract)?;
-            
+
             // Initialize SLED storage
             let storage = StorageSled::new(&cli.storage_path)?;
-            
-            // Store the contract address
-            storage.store_contract_address(contract_address.clone())?;
-            
-            // Sync blockchain data to local storage
-            storage.sync_from_blockchain(&api, contract_address).await?;
-            
-            println!("Blockchain data synced to local storage");
+            let transcoder = Transcoder::load(std::path::Path::new(&cli.metadata_path))?;
+
+            // Sync blockchain data to local storage, advancing from the
+            // last synced block and reconciling any reorg since the
+            // previous run, then refreshing cached token/balance records
+            // against the new tip.
+            let synced = storage_sled::sync_from_blockchain(
+                &storage,
+                &api,
+                contract_address,
+                Some(&transcoder.storage_layout()),
+            )
+            .await?;
+
+            println!("Blockchain data synced to local storage ({} new block(s))", synced);
         },
-        Commands::Deploy => {
-            deploy_contract(&api, &signer).await?;
+        Commands::Deploy { dry_run, gas_margin } => {
+            deploy_contract(&api, &signer, dry_run, gas_margin).await?;
         },
-        Commands::CreateToken { contract, uri, supply } => {
+        Commands::CreateToken { contract, uri, supply, decimals, dry_run, gas_margin } => {
             let contract_address = AccountId::from_str(&contract)?;
-            create_token(&api, &signer, contract_address, uri, supply).await?;
-            
+            let transcoder = Transcoder::load(std::path::Path::new(&cli.metadata_path))?;
+            create_token(&api, &signer, &transcoder, contract_address, uri, supply, dry_run, gas_margin, decimals).await?;
+
             // Also store token in local SLED storage
             let storage = StorageSled::new(&cli.storage_path)?;
-            
+
             // Store the contract address
             storage.store_contract_address(contract_address.clone())?;
-            
+
             // Create a token object (with ID 1 as a placeholder - in a real impl this would be retrieved from events)
             let token = Token {
                 id: 1, // Placeholder
@@ -333,21 +546,29 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 creator: AccountId::from(signer.account_id().0),
                 total_supply: supply,
                 price_threshold: None,
+                decimals,
             };
-            
+
             // Store the token
             storage.store_token(token)?;
-            
+
             println!("Token also stored in local SLED database");
         },
-        Commands::Transfer { contract, to, token_id, amount } => {
+        Commands::Transfer { contract, to, token_id, amount, dry_run, gas_margin } => {
             let contract_address = AccountId::from_str(&contract)?;
             let to_address = AccountId::from_str(&to)?;
-            transfer_tokens(&api, &signer, contract_address, to_address, token_id, amount).await?;
-            
+            let transcoder = Transcoder::load(std::path::Path::new(&cli.metadata_path))?;
+
             // Update balances in local SLED storage
             let storage = StorageSled::new(&cli.storage_path)?;
-            
+            let decimals = storage
+                .get_token(token_id)?
+                .map(|t| t.decimals)
+                .unwrap_or(DEFAULT_DECIMALS);
+            let amount = parse_amount(&amount, decimals)?;
+
+            transfer_tokens(&api, &signer, &transcoder, contract_address, to_address, token_id, amount, dry_run, gas_margin).await?;
+
             // Get current balance of sender
             let from_address = AccountId::from(signer.account_id().0);
             let sender_balance = storage.get_balance(&from_address, token_id)?;
@@ -373,15 +594,63 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             
             println!("Balances updated in local SLED database");
         },
+        Commands::TransferBatch { contract, transfers } => {
+            let contract_address = AccountId::from_str(&contract)?;
+            let transcoder = Transcoder::load(std::path::Path::new(&cli.metadata_path))?;
+            let storage = StorageSled::new(&cli.storage_path)?;
+
+            let mut parsed = Vec::with_capacity(transfers.len());
+            for entry in &transfers {
+                let mut parts = entry.splitn(3, ':');
+                let to = parts.next().ok_or(CliError::ParseError)?;
+                let token_id: u128 = parts
+                    .next()
+                    .ok_or(CliError::ParseError)?
+                    .parse()
+                    .map_err(|_| CliError::ParseError)?;
+                let raw_amount = parts.next().ok_or(CliError::ParseError)?;
+
+                let to_address = AccountId::from_str(to)?;
+                let decimals = storage
+                    .get_token(token_id)?
+                    .map(|t| t.decimals)
+                    .unwrap_or(DEFAULT_DECIMALS);
+                let amount = parse_amount(raw_amount, decimals)?;
+
+                parsed.push((to_address, token_id, amount));
+            }
+
+            transfer_batch(&api, &signer, &transcoder, contract_address, parsed).await?;
+        },
         Commands::Balance { contract, account, token_id } => {
             let contract_address = AccountId::from_str(&contract)?;
             let account_address = AccountId::from_str(&account)?;
-            check_balance(&api, contract_address, account_address, token_id).await?;
+            let transcoder = Transcoder::load(std::path::Path::new(&cli.metadata_path))?;
+            let storage = StorageSled::new(&cli.storage_path)?;
+            let decimals = storage
+                .get_token(token_id)?
+                .map(|t| t.decimals)
+                .unwrap_or(DEFAULT_DECIMALS);
+            check_balance(&api, &transcoder, contract_address, account_address, token_id, decimals).await?;
         },
-        Commands::VerifyStorage { contract, block_number } => {
+        Commands::VerifyStorage { contract, block_number, decimals } => {
             let contract_address = AccountId::from_str(&contract)?;
             let block = block_number.unwrap_or(0); // 0 means latest block
-            storage_validator::display_contract_state(&api, contract_address, block).await?;
+            let transcoder = Transcoder::load(std::path::Path::new(&cli.metadata_path))?;
+            storage_validator::display_contract_state(
+                &storage_validator::ProvedBackend::new(&api),
+                &transcoder.storage_layout(),
+                contract_address,
+                block,
+                storage_validator::PARALLEL_QUERY_BATCH_SIZE,
+                decimals,
+            ).await?;
+        },
+        Commands::DumpStorage { contract, block_number } => {
+            let contract_address = AccountId::from_str(&contract)?;
+            let block = block_number.unwrap_or(0); // 0 means latest block
+            let transcoder = Transcoder::load(std::path::Path::new(&cli.metadata_path))?;
+            storage_validator::dump_storage(&api, &transcoder, contract_address, block).await?;
         },
     }
     
@@ -392,6 +661,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 async fn deploy_contract<T: subxt::Config>(
     api: &OnlineClient<T>,
     signer: &PairSigner<T, sr25519::Pair>,
+    dry_run: bool,
+    gas_margin: u8,
 ) -> Result<(), Box<dyn std::error::Error>>
 where
     T::AccountId: From<[u8; 32]>,
@@ -455,18 +726,53 @@ where
     // Salt for address generation (using a random value)
     let mut salt = [0u8; 32];
     getrandom::getrandom(&mut salt)?;
-    
+
+    // Dry-run the instantiation to obtain gas_required/storage_deposit
+    // instead of blindly reserving a fixed 10_000_000_000 weight.
+    let origin = AccountId32::from(signer.account_id().0);
+    let estimate = dry_run_instantiate(
+        api,
+        origin,
+        0u128,
+        contract_wasm.clone(),
+        data.clone(),
+        salt.to_vec(),
+    )
+    .await?;
+
+    let gas_required = estimate
+        .result
+        .as_ref()
+        .map(|r| r.gas_required)
+        .unwrap_or(10_000_000_000u64);
+    let gas_limit = apply_gas_margin(gas_required, gas_margin);
+    let storage_deposit = estimate
+        .result
+        .as_ref()
+        .ok()
+        .and_then(|r| storage_deposit_limit(&r.storage_deposit));
+
+    println!(
+        "Dry-run estimate: gas_required={}, gas_limit(+{}%)={}, storage_deposit={:?}",
+        gas_required, gas_margin, gas_limit, storage_deposit
+    );
+
+    if dry_run {
+        println!("--dry-run set, not submitting the instantiate extrinsic");
+        return Ok(());
+    }
+
     let instantiate_tx = substrate::tx()
         .contracts()
         .instantiate_with_code(
             0u128, // endowment
-            10_000_000_000u64, // gas_limit
-            None, // storage_deposit_limit
+            gas_limit,
+            storage_deposit,
             contract_wasm,
             data, // constructor args
             salt.to_vec(),
         );
-    
+
     // Submit the transaction and wait for it to be included in a block
     let instantiate_progress = api
         .tx()
@@ -504,40 +810,68 @@ where
 async fn create_token<T: subxt::Config>(
     api: &OnlineClient<T>,
     signer: &PairSigner<T, sr25519::Pair>,
+    transcoder: &Transcoder,
     contract_address: AccountId,
     uri: String,
     initial_supply: u128,
+    dry_run: bool,
+    gas_margin: u8,
+    decimals: u8,
 ) -> Result<(), Box<dyn std::error::Error>>
 where
+    T: subxt::Config<Hash = H256>,
     T::AccountId: From<[u8; 32]>,
     <T as subxt::Config>::Address: From<T::AccountId>,
 {
     println!("Creating a new token in contract {}", contract_address);
-    
-    // Prepare contract call data for create_token
-    let params = CreateTokenParams {
-        uri,
-        initial_supply,
-    };
-    
-    // Selector for create_token function - compute proper selector
-    let selector = compute_selector("createToken(string,uint128)");
-    
-    // Encode the message: selector + params
-    let mut message = selector.to_vec();
-    message.extend(params.encode());
-    
+
+    // Encode selector + SCALE args from the contract's own metadata,
+    // instead of a hand-rolled Ethereum-style selector.
+    let message = transcoder.encode_call(
+        "create_token",
+        vec![
+            TranscoderValue::String(uri),
+            TranscoderValue::U128(initial_supply),
+        ],
+    )?;
+
+    let origin = AccountId32::from(signer.account_id().0);
+    let dest = AccountId32::from(contract_address.0);
+    let estimate = dry_run_call(api, origin, dest, 0u128, message.clone()).await?;
+    let gas_limit = apply_gas_margin(estimate.gas_required, gas_margin);
+    let storage_deposit = storage_deposit_limit(&estimate.storage_deposit);
+
+    println!(
+        "Dry-run estimate: gas_required={}, gas_limit(+{}%)={}, storage_deposit={:?}",
+        estimate.gas_required, gas_margin, gas_limit, storage_deposit
+    );
+
+    if let Ok(ref exec) = estimate.result {
+        if Transcoder::is_reverted(exec.flags) {
+            return Err(format!(
+                "create_token would revert: {}",
+                Transcoder::decode_revert_reason(&exec.data)
+            )
+            .into());
+        }
+    }
+
+    if dry_run {
+        println!("--dry-run set, not submitting the create_token extrinsic");
+        return Ok(());
+    }
+
     // Create contract call transaction
     let contract_call_tx = substrate::tx()
         .contracts()
         .call(
             T::AccountId::from(contract_address.0), // Contract address
             0u128,                                  // value to transfer
-            10_000_000_000u64,                      // gas limit
-            None,                                   // storage deposit limit
+            gas_limit,
+            storage_deposit,
             message,                                // encoded message
         );
-    
+
     // Submit transaction
     let tx_progress = api
         .tx()
@@ -552,19 +886,20 @@ where
     for event in tx_events.find_events::<substrate::contracts::events::ContractEmitted>() {
         if let Ok(ev) = event {
             if ev.contract == T::AccountId::from(contract_address.0) {
-                println!("Contract emitted event with data: 0x{}", hex::encode(&ev.data));
-                
-                // Extract token ID from event data
-                // The event format should be TokenCreated(uint128,AccountId,string)
-                // First 4 bytes are the event signature, then the token ID (16 bytes)
-                if ev.data.len() >= 20 {
-                    let event_selector = &ev.data[0..4];
-                    // Check if this is the TokenCreated event
-                    if event_selector == &compute_selector("TokenCreated(uint128,address,string)")[..] {
-                        let mut id_bytes = [0u8; 16];
-                        id_bytes.copy_from_slice(&ev.data[4..20]);
-                        token_id = Some(u128::from_le_bytes(id_bytes));
+                match transcoder.decode_event(&ev.data) {
+                    Some((label, fields)) => {
+                        println!("Contract emitted: {}", Transcoder::format_event(&label, &fields));
+                        if label == "TokenCreated" {
+                            token_id = fields.iter().find_map(|f| match (&f.label, &f.value) {
+                                (l, TranscoderValue::U128(id)) if l == "id" => Some(*id),
+                                _ => None,
+                            });
+                        }
                     }
+                    None => println!(
+                        "Contract emitted an event that doesn't match any known type: 0x{}",
+                        hex::encode(&ev.data)
+                    ),
                 }
             }
         }
@@ -577,15 +912,17 @@ where
         println!("\nVerifying token storage on-chain:");
         
         // Check the URI directly from storage
-        match storage_validator::verify_token_uri(api, contract_address.clone(), id).await {
+        let layout = transcoder.storage_layout();
+        match storage_validator::verify_token_uri(&storage_validator::ProvedBackend::new(api), &layout, contract_address.clone(), id).await {
             Ok(uri) => println!("Token URI verified: {}", uri),
             Err(e) => println!("Failed to verify token URI: {}", e),
         }
-        
+
         // Check the creator's balance
         let creator_account = AccountId::from(signer.account_id().0);
-        match storage_validator::verify_token_balance(api, contract_address, creator_account, id).await {
-            Ok(balance) => println!("Creator's balance verified: {}", balance),
+        match storage_validator::verify_token_balance(&storage_validator::ProvedBackend::new(api), &layout, contract_address, creator_account, id, decimals).await {
+            Ok(Some(balance)) => println!("Creator's balance verified: {} ({})", balance, format_amount(balance, decimals)),
+            Ok(None) => println!("Creator's balance verified: 0 (no storage entry)"),
             Err(e) => println!("Failed to verify creator's balance: {}", e),
         }
     } else {
@@ -600,44 +937,69 @@ where
 async fn transfer_tokens<T: subxt::Config>(
     api: &OnlineClient<T>,
     signer: &PairSigner<T, sr25519::Pair>,
+    transcoder: &Transcoder,
     contract_address: AccountId,
     to: AccountId,
     token_id: u128,
     amount: u128,
+    dry_run: bool,
+    gas_margin: u8,
 ) -> Result<(), Box<dyn std::error::Error>>
 where
     T::AccountId: From<[u8; 32]>,
     <T as subxt::Config>::Address: From<T::AccountId>,
 {
     println!("Transferring {} tokens with ID {} to {}", amount, token_id, to);
-    
-    // Prepare contract call data for safe_transfer_from
-    let params = TransferParams {
-        from: AccountId32::from(signer.account_id().0),
-        to: AccountId32::from(to.0),
-        id: token_id,
-        amount,
-        data: Vec::new(),
-    };
-    
-    // Selector for safe_transfer_from function - compute proper selector
-    let selector = compute_selector("safeTransferFrom(address,address,uint128,uint128,bytes)");
-    
-    // Encode the message: selector + params
-    let mut message = selector.to_vec();
-    message.extend(params.encode());
-    
+
+    let from = AccountId::from(signer.account_id().0);
+    let message = transcoder.encode_call(
+        "safe_transfer_from",
+        vec![
+            TranscoderValue::AccountId(from),
+            TranscoderValue::AccountId(to),
+            TranscoderValue::U128(token_id),
+            TranscoderValue::U128(amount),
+            TranscoderValue::Bytes(Vec::new()),
+        ],
+    )?;
+
+    let origin = AccountId32::from(signer.account_id().0);
+    let dest = AccountId32::from(contract_address.0);
+    let estimate = dry_run_call(api, origin, dest, 0u128, message.clone()).await?;
+    let gas_limit = apply_gas_margin(estimate.gas_required, gas_margin);
+    let storage_deposit = storage_deposit_limit(&estimate.storage_deposit);
+
+    println!(
+        "Dry-run estimate: gas_required={}, gas_limit(+{}%)={}, storage_deposit={:?}",
+        estimate.gas_required, gas_margin, gas_limit, storage_deposit
+    );
+
+    if let Ok(ref exec) = estimate.result {
+        if Transcoder::is_reverted(exec.flags) {
+            return Err(format!(
+                "transfer would revert: {}",
+                Transcoder::decode_revert_reason(&exec.data)
+            )
+            .into());
+        }
+    }
+
+    if dry_run {
+        println!("--dry-run set, not submitting the transfer extrinsic");
+        return Ok(());
+    }
+
     // Create contract call transaction
     let contract_call_tx = substrate::tx()
         .contracts()
         .call(
             T::AccountId::from(contract_address.0), // Contract address
             0u128,                                  // value to transfer
-            10_000_000_000u64,                      // gas limit
-            None,                                   // storage deposit limit
+            gas_limit,
+            storage_deposit,
             message,                                // encoded message
         );
-    
+
     // Submit transaction
     let tx_progress = api
         .tx()
@@ -652,71 +1014,139 @@ where
     for event in tx_events.find_events::<substrate::contracts::events::ContractEmitted>() {
         if let Ok(ev) = event {
             if ev.contract == T::AccountId::from(contract_address.0) {
-                println!("Contract emitted event with data: 0x{}", hex::encode(&ev.data));
+                match transcoder.decode_event(&ev.data) {
+                    Some((label, fields)) => {
+                        println!("Contract emitted: {}", Transcoder::format_event(&label, &fields))
+                    }
+                    None => println!(
+                        "Contract emitted an event that doesn't match any known type: 0x{}",
+                        hex::encode(&ev.data)
+                    ),
+                }
             }
         }
     }
-    
+
+    Ok(())
+}
+
+/// Transfers tokens to several recipients in one batch. Each extrinsic is
+/// signed with a nonce drawn from a shared `NonceManager` and submitted
+/// without waiting for inclusion, so the nonce for transfer N+1 doesn't
+/// wait on transfer N finalizing; each submission's finalization is then
+/// awaited in turn.
+async fn transfer_batch<T: subxt::Config>(
+    api: &OnlineClient<T>,
+    signer: &PairSigner<T, sr25519::Pair>,
+    transcoder: &Transcoder,
+    contract_address: AccountId,
+    transfers: Vec<(AccountId, u128, u128)>,
+) -> Result<(), Box<dyn std::error::Error>>
+where
+    T::AccountId: From<[u8; 32]>,
+    <T as subxt::Config>::Address: From<T::AccountId>,
+{
+    println!("Submitting a batch of {} transfers", transfers.len());
+
+    let from = AccountId::from(signer.account_id().0);
+    let nonces = NonceManager::new();
+    let mut progresses = Vec::with_capacity(transfers.len());
+
+    for (to, token_id, amount) in &transfers {
+        let message = transcoder.encode_call(
+            "safe_transfer_from",
+            vec![
+                TranscoderValue::AccountId(from),
+                TranscoderValue::AccountId(*to),
+                TranscoderValue::U128(*token_id),
+                TranscoderValue::U128(*amount),
+                TranscoderValue::Bytes(Vec::new()),
+            ],
+        )?;
+
+        let contract_call_tx = substrate::tx().contracts().call(
+            T::AccountId::from(contract_address.0),
+            0u128,
+            10_000_000_000u64,
+            None,
+            message,
+        );
+
+        let progress = client::submit_with_nonce(api, signer, &nonces, &contract_call_tx).await?;
+        progresses.push((*to, *token_id, *amount, progress));
+    }
+
+    for (to, token_id, amount, progress) in progresses {
+        match progress.wait_for_finalized_success().await {
+            Ok(_) => println!("Transferred {} of token {} to {}", amount, token_id, to),
+            Err(e) => eprintln!("Transfer of token {} to {} failed: {}", token_id, to, e),
+        }
+    }
+
     Ok(())
 }
 
 /// Checks the balance of an account for a specific token
 async fn check_balance<T: subxt::Config>(
     api: &OnlineClient<T>,
+    transcoder: &Transcoder,
     contract_address: AccountId,
     account: AccountId,
     token_id: u128,
-) -> Result<(), Box<dyn std::error::Error>> {
+    decimals: u8,
+) -> Result<(), Box<dyn std::error::Error>>
+where
+    T: subxt::Config<Hash = H256>,
+    T::AccountId: From<[u8; 32]>,
+{
     println!("Checking balance of account {} for token ID {}", account, token_id);
-    
-    // First try to read directly from storage
+
+    // First try to read directly from storage, proof-verified against the
+    // queried block's state_root rather than trusted RPC response.
     let balance = storage_validator::verify_token_balance(
-        api, 
-        contract_address.clone(), 
-        account.clone(), 
-        token_id
+        &storage_validator::ProvedBackend::new(api),
+        &transcoder.storage_layout(),
+        contract_address.clone(),
+        account.clone(),
+        token_id,
+        decimals,
     ).await?;
-    
-    println!("Token balance from storage: {}", balance);
-    
-    // In addition, query using a contract call
-    // Prepare contract call data for balance_of
-    let params = BalanceOfParams {
-        account: AccountId32::from(account.0),
-        id: token_id,
-    };
-    
-    // Selector for balance_of function
-    let selector = [0x00, 0x01, 0x02, 0x03]; 
-    
-    // Encode the message: selector + params
-    let mut message = selector.to_vec();
-    message.extend(params.encode());
-    
-    // Create contract call for read-only query
-    let result = api.rpc().state_call(
-        "ContractsApi_call",
-        scale::Encode::encode(&(
-            T::AccountId::from(contract_address.0), // Contract address
-            0u128,                                  // value to transfer
-            10_000_000_000u64,                      // gas limit
-            None::<()>,                             // storage deposit limit
-            message,                                // encoded message
-        )).as_slice(),
-    ).await?;
-    
-    if !result.is_empty() {
-        // Decode the result
-        if result.len() >= 16 {
-            let mut balance_bytes = [0u8; 16];
-            balance_bytes.copy_from_slice(&result[0..16]);
-            let contract_balance = u128::from_le_bytes(balance_bytes);
-            println!("Balance from contract call: {}", contract_balance);
-        } else {
-            println!("Couldn't decode balance from contract call");
-        }
-    } else {
-        println!("No result returned from contract call");
+    let balance = balance.unwrap_or(0);
+
+    println!("Token balance from storage: {}", format_amount(balance, decimals));
+
+    // In addition, query using a contract call, transcoded from metadata
+    // instead of the `[0x00, 0x01, 0x02, 0x03]` placeholder selector.
+    let message = transcoder.encode_call(
+        "balance_of",
+        vec![
+            TranscoderValue::AccountId(account.clone()),
+            TranscoderValue::U128(token_id),
+        ],
+    )?;
+
+    // Dry-run the call through the same `ContractsApi_call` path the write
+    // functions use, rather than hand-rolling a one-off RPC encode that
+    // drops the `origin` argument and leaves the returned
+    // `ContractExecResult` undecoded.
+    let origin = AccountId32::from(account.0);
+    let dest = AccountId32::from(contract_address.0);
+    let estimate = dry_run_call(api, origin, dest, 0u128, message).await?;
+
+    match estimate.result {
+        Ok(exec) if Transcoder::is_reverted(exec.flags) => {
+            println!(
+                "Balance query reverted: {}",
+                Transcoder::decode_revert_reason(&exec.data)
+            );
+        },
+        Ok(exec) => match u128::decode(&mut &exec.data[..]) {
+            Ok(contract_balance) => {
+                println!("Balance from contract call: {}", format_amount(contract_balance, decimals));
+            },
+            Err(e) => println!("Couldn't decode balance from contract call: {}", e),
+        },
+        Err(()) => println!("Balance query trapped (ExecError)"),
     }
     
     Ok(())
@@ -1,24 +1,172 @@
 use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::Duration;
+use futures::StreamExt;
 use tokio::runtime::Runtime;
 use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::Message;
 use sp_core::{sr25519, Pair};
 use subxt::{tx::PairSigner, OnlineClient, PolkadotConfig};
 use ink::env::AccountId;
 
-use crate::storage_sled::{StorageSled, PriceListener, PriceAction};
+use crate::storage_sled::{self, StorageSled, TypedStorage, PriceListener, PriceAction};
+use crate::client::NonceManager;
 use crate::error::CliError;
 
-/// Struct to manage price listening and automatic execution of ERC1155 transactions
-pub struct PriceListenerService {
-    storage: Arc<StorageSled>,
+/// A source of price ticks for a single symbol. Implementations poll an
+/// external quote source (HTTP REST endpoint, WebSocket feed, etc.) so
+/// `PriceListenerService` can run unattended instead of depending on manual
+/// `UpdatePrice` commands.
+pub trait PriceFeed: Send + Sync {
+    /// The symbol this feed quotes, e.g. "DOT/USD".
+    fn symbol(&self) -> &str;
+
+    /// Fetches the current price in the token's base units.
+    fn fetch_price(&self) -> Result<u128, Box<dyn std::error::Error>>;
+}
+
+/// A `PriceFeed` that polls a JSON HTTP endpoint expected to respond with
+/// `{"price": <integer base units>}`.
+pub struct HttpPriceFeed {
+    url: String,
+    symbol: String,
+}
+
+impl HttpPriceFeed {
+    pub fn new(url: String, symbol: String) -> Self {
+        Self { url, symbol }
+    }
+}
+
+impl PriceFeed for HttpPriceFeed {
+    fn symbol(&self) -> &str {
+        &self.symbol
+    }
+
+    fn fetch_price(&self) -> Result<u128, Box<dyn std::error::Error>> {
+        let body = ureq::get(&self.url)
+            .query("symbol", &self.symbol)
+            .call()?
+            .into_string()?;
+        let parsed: serde_json::Value = serde_json::from_str(&body)?;
+        parsed["price"]
+            .as_u64()
+            .map(|p| p as u128)
+            .ok_or_else(|| Box::new(CliError::ParseError) as Box<dyn std::error::Error>)
+    }
+}
+
+/// Aggregates quotes from multiple feeds into a single median price, so one
+/// stale or manipulated feed cannot move the listener's execution price on
+/// its own. Feeds that fail to respond are skipped rather than failing the
+/// whole tick.
+pub fn median_price(feeds: &[Box<dyn PriceFeed>]) -> Option<u128> {
+    let mut prices: Vec<u128> = feeds
+        .iter()
+        .filter_map(|feed| match feed.fetch_price() {
+            Ok(price) => Some(price),
+            Err(e) => {
+                eprintln!("Price feed '{}' failed: {}", feed.symbol(), e);
+                None
+            }
+        })
+        .collect();
+
+    if prices.is_empty() {
+        return None;
+    }
+
+    prices.sort_unstable();
+    Some(prices[prices.len() / 2])
+}
+
+/// Decodes a single WebSocket ticker message into a price update, so
+/// different feeds' JSON schemas can be mapped onto token IDs without
+/// changing `PriceFeedSource` itself. Returns `None` for messages that
+/// don't carry a price tick (heartbeats, subscription acks, etc.).
+pub type PriceMessageDecoder = Arc<dyn Fn(&str) -> Option<PriceUpdate> + Send + Sync>;
+
+/// Delay before the first WebSocket reconnect attempt after a disconnect;
+/// doubles on each subsequent failure up to `MAX_RECONNECT_DELAY`.
+const INITIAL_RECONNECT_DELAY: Duration = Duration::from_secs(1);
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(60);
+
+/// A persistent WebSocket price feed: connects to `url`, decodes streamed
+/// ticker messages via `decode`, and forwards them into the channel `start`
+/// drains. This is what turns the service from a push-only stub (depending
+/// on manual `UpdatePrice` calls) into an always-on executor.
+pub struct PriceFeedSource {
+    url: String,
+    decode: PriceMessageDecoder,
+}
+
+impl PriceFeedSource {
+    pub fn new(url: String, decode: PriceMessageDecoder) -> Self {
+        Self { url, decode }
+    }
+
+    /// Runs the feed until `running` is cleared, reconnecting with
+    /// exponential backoff whenever the socket drops or fails to connect.
+    async fn run(&self, tx: mpsc::Sender<PriceUpdate>, running: Arc<Mutex<bool>>) {
+        let mut delay = INITIAL_RECONNECT_DELAY;
+        while *running.lock().unwrap() {
+            match tokio_tungstenite::connect_async(&self.url).await {
+                Ok((stream, _)) => {
+                    delay = INITIAL_RECONNECT_DELAY;
+                    let (_, mut read) = stream.split();
+                    while *running.lock().unwrap() {
+                        match read.next().await {
+                            Some(Ok(Message::Text(text))) => {
+                                if let Some(update) = (self.decode)(&text) {
+                                    if tx.send(update).await.is_err() {
+                                        return;
+                                    }
+                                }
+                            }
+                            Some(Ok(_)) => continue,
+                            Some(Err(e)) => {
+                                eprintln!("WebSocket feed '{}' error: {}", self.url, e);
+                                break;
+                            }
+                            None => break,
+                        }
+                    }
+                }
+                Err(e) => {
+                    eprintln!("Failed to connect to WebSocket feed '{}': {}", self.url, e);
+                }
+            }
+
+            if !*running.lock().unwrap() {
+                break;
+            }
+            tokio::time::sleep(delay).await;
+            delay = (delay * 2).min(MAX_RECONNECT_DELAY);
+        }
+    }
+}
+
+/// Struct to manage price listening and automatic execution of ERC1155 transactions.
+///
+/// Generic over the storage backend so tests can drive it with
+/// `StorageMemory` instead of standing up a temp directory and a real sled
+/// database; `new` wires up the sled-backed default used in production.
+pub struct PriceListenerService<S: TypedStorage + 'static = StorageSled> {
+    storage: Arc<S>,
     client: Arc<OnlineClient<PolkadotConfig>>,
     runtime: Runtime,
     running: Arc<Mutex<bool>>,
     // Channel for receiving price updates
     tx: mpsc::Sender<PriceUpdate>,
     rx: Arc<Mutex<mpsc::Receiver<PriceUpdate>>>,
+    /// Shared nonce cache for the signer used by `process_price_update`, so
+    /// concurrent automatic sells/buys fired in quick succession draw
+    /// sequential nonces from one counter instead of each reading the same
+    /// stale on-chain value and colliding.
+    nonces: Arc<NonceManager>,
+    /// Optional streaming feed registered via `set_websocket_feed`, spawned
+    /// alongside the other background threads by `start`.
+    websocket_feed: Mutex<Option<Arc<PriceFeedSource>>>,
 }
 
 /// Represents a price update for a token
@@ -27,28 +175,39 @@ pub struct PriceUpdate {
     pub price: u128,
 }
 
-impl PriceListenerService {
-    /// Create a new price listener service
+/// How often the feed-polling thread re-checks each configured listener's feed.
+const FEED_POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+impl PriceListenerService<StorageSled> {
+    /// Create a new price listener service backed by a sled database on disk
     pub fn new(
         storage_path: &str,
         node_url: &str,
     ) -> Result<Self, Box<dyn std::error::Error>> {
-        // Initialize SLED storage
-        let storage = Arc::new(StorageSled::new(storage_path)?);
-        
+        Self::with_storage(Arc::new(StorageSled::new(storage_path)?), node_url)
+    }
+}
+
+impl<S: TypedStorage + 'static> PriceListenerService<S> {
+    /// Create a new price listener service over any `TypedStorage` backend,
+    /// e.g. `StorageMemory` for tests that shouldn't need a temp directory.
+    pub fn with_storage(
+        storage: Arc<S>,
+        node_url: &str,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
         // Create a Tokio runtime for async operations
         let runtime = Runtime::new()?;
-        
+
         // Initialize Substrate client
         let client = runtime.block_on(async {
             OnlineClient::<PolkadotConfig>::from_url(node_url).await
         })?;
-        
+
         let client = Arc::new(client);
-        
+
         // Create a channel for price updates
         let (tx, rx) = mpsc::channel::<PriceUpdate>(100);
-        
+
         Ok(Self {
             storage,
             client,
@@ -56,8 +215,17 @@ impl PriceListenerService {
             running: Arc::new(Mutex::new(false)),
             tx,
             rx: Arc::new(Mutex::new(rx)),
+            nonces: Arc::new(NonceManager::new()),
+            websocket_feed: Mutex::new(None),
         })
     }
+
+    /// Registers a persistent WebSocket price feed to stream ticks from
+    /// once `start` is called, instead of (or alongside) the per-listener
+    /// HTTP polling and manual `UpdatePrice` calls.
+    pub fn set_websocket_feed(&self, url: String, decode: PriceMessageDecoder) {
+        *self.websocket_feed.lock().unwrap() = Some(Arc::new(PriceFeedSource::new(url, decode)));
+    }
     
     /// Start the price listener service
     pub fn start(&self, seed: &str) -> Result<(), Box<dyn std::error::Error>> {
@@ -74,6 +242,7 @@ impl PriceListenerService {
         let client = self.client.clone();
         let running = self.running.clone();
         let rx = self.rx.clone();
+        let nonces = self.nonces.clone();
         
         // Create a key pair for signing transactions
         let pair = sr25519::Pair::from_string(seed, None)?;
@@ -91,7 +260,11 @@ impl PriceListenerService {
                     
                     // Process the price update and execute transactions if needed
                     runtime.block_on(async {
-                        match storage.process_price_update(&client, token_id, price, &signer) {
+                        // No contract metadata is wired into the service
+                        // today (see `sync_blockchain_data`'s equivalent
+                        // limitation), so Transfer actions still only
+                        // simulate their submission here.
+                        match storage_sled::process_price_update(&*storage, &client, token_id, price, &signer, &nonces, None).await {
                             Ok(executed) => {
                                 if executed {
                                     println!("Executed transaction for token {} at price {}", token_id, price);
@@ -109,10 +282,58 @@ impl PriceListenerService {
             }
         });
         
+        // Spawn a second thread that polls each listener's configured feed
+        // (if any) on a fixed interval and pushes ticks through the same
+        // channel the processing thread above drains, so `StartPriceListener`
+        // can run unattended instead of depending on manual `UpdatePrice`.
+        let feed_storage = self.storage.clone();
+        let feed_running = self.running.clone();
+        let feed_tx = self.tx.clone();
+        thread::spawn(move || {
+            while *feed_running.lock().unwrap() {
+                match feed_storage.get_price_listeners() {
+                    Ok(listeners) => {
+                        for listener in listeners.iter().filter(|l| l.enabled) {
+                            let (Some(url), Some(symbol)) =
+                                (listener.feed_url.clone(), listener.feed_symbol.clone())
+                            else {
+                                continue;
+                            };
+                            let feeds: Vec<Box<dyn PriceFeed>> =
+                                vec![Box::new(HttpPriceFeed::new(url, symbol))];
+                            if let Some(price) = median_price(&feeds) {
+                                if feed_tx.blocking_send(PriceUpdate {
+                                    token_id: listener.token_id,
+                                    price,
+                                }).is_err() {
+                                    eprintln!("Failed to forward feed tick for token {}", listener.token_id);
+                                }
+                            }
+                        }
+                    }
+                    Err(e) => eprintln!("Failed to load price listeners for feed polling: {}", e),
+                }
+
+                thread::sleep(FEED_POLL_INTERVAL);
+            }
+        });
+
+        // Spawn a third thread running the registered WebSocket feed (if
+        // any), streaming ticks into the same channel as the two threads
+        // above for as long as the service is running.
+        if let Some(feed) = self.websocket_feed.lock().unwrap().clone() {
+            let feed_running = self.running.clone();
+            let feed_tx = self.tx.clone();
+            thread::spawn(move || {
+                let runtime = Runtime::new().expect("Failed to create runtime");
+                runtime.block_on(feed.run(feed_tx, feed_running));
+            });
+        }
+
         println!("Price listener service started");
         Ok(())
     }
-    
+
     /// Stop the price listener service
     pub fn stop(&self) -> Result<(), Box<dyn std::error::Error>> {
         let mut running = self.running.lock().unwrap();
@@ -120,20 +341,28 @@ impl PriceListenerService {
         Ok(())
     }
     
-    /// Create a new price listener for a token
+    /// Create a new price listener for a token, optionally wiring it to an
+    /// external feed (`feed_url`/`feed_symbol`) so it can be driven by
+    /// `StartPriceListener` without manual `UpdatePrice` calls.
     pub fn create_price_listener(
         &self,
         token_id: u128,
         target_price: u128,
         action: PriceAction,
+        feed_url: Option<String>,
+        feed_symbol: Option<String>,
+        quote: Option<storage_sled::PriceQuote>,
     ) -> Result<(), Box<dyn std::error::Error>> {
         let listener = PriceListener {
             token_id,
             target_price,
             action,
             enabled: true,
+            feed_url,
+            feed_symbol,
+            quote,
         };
-        
+
         self.storage.set_price_listener(listener)?;
         println!("Created price listener for token {} at price {}", token_id, target_price);
         Ok(())
@@ -159,14 +388,19 @@ impl PriceListenerService {
     pub fn sync_blockchain_data(&self) -> Result<(), Box<dyn std::error::Error>> {
         // Get the contract address from storage
         if let Some(contract_address) = self.storage.get_contract_address()? {
-            self.runtime.block_on(async {
-                self.storage.sync_from_blockchain(&self.client, contract_address).await
+            // The service has no contract metadata path wired in, so it
+            // can't derive storage keys to refresh cached token/balance
+            // records — it only advances the synced-block window and
+            // tracks reorgs. The CLI's `sync-storage` command, which does
+            // have a metadata path, passes a layout and gets full refresh.
+            let synced = self.runtime.block_on(async {
+                storage_sled::sync_from_blockchain(&*self.storage, &self.client, contract_address, None).await
             })?;
-            println!("Synchronized blockchain data to local storage");
+            println!("Synchronized blockchain data to local storage ({} new block(s))", synced);
         } else {
             println!("No contract address found in storage. Please deploy or set contract address first.");
         }
-        
+
         Ok(())
     }
 } 
\ No newline at end of file
@@ -1,9 +1,26 @@
-use sled::{Db, IVec};
+use sled::Db;
 use ink::env::AccountId;
 use codec::{Encode, Decode};
 use sp_core::H256;
-use std::convert::TryInto;
-use std::sync::Arc;
+use std::collections::BTreeMap;
+use std::sync::{Arc, RwLock};
+
+use crate::client::NonceManager;
+use crate::denomination::{format_amount, DEFAULT_DECIMALS};
+use crate::storage_validator::{self, SubxtBackend};
+use crate::transcoder::{StorageLayout, Transcoder, TranscoderValue};
+use crate::tx_pipeline::{AuditLogger, FeeEstimatingSubmitter, RetryLayer, SlippageGuard, TxPipeline};
+
+/// The key/value verbs a storage backend must support. Everything else
+/// (token/balance/listener encoding, prefix scans for typed records) is
+/// implemented once, generically, on top of this in `TypedStorage` — a
+/// backend only has to provide raw bytes-in, bytes-out storage.
+pub trait Storage: Send + Sync {
+    fn put(&self, key: &[u8], value: Vec<u8>) -> Result<(), Box<dyn std::error::Error>>;
+    fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, Box<dyn std::error::Error>>;
+    fn scan_prefix(&self, prefix: &[u8]) -> Result<Vec<Vec<u8>>, Box<dyn std::error::Error>>;
+    fn flush(&self) -> Result<(), Box<dyn std::error::Error>>;
+}
 
 /// StorageSled provides a local persistent storage solution for ERC1155 contract data
 /// This acts as a local cache and backup for on-chain data, enabling faster reads
@@ -12,6 +29,86 @@ pub struct StorageSled {
     db: Arc<Db>,
 }
 
+impl StorageSled {
+    /// Open or create a new SLED database for ERC1155 storage
+    pub fn new(path: &str) -> Result<Self, sled::Error> {
+        let db = sled::open(path)?;
+        Ok(Self { db: Arc::new(db) })
+    }
+
+    /// Closes the database
+    pub fn close(self) -> Result<(), sled::Error> {
+        Arc::try_unwrap(self.db)
+            .expect("There are other references to the database")
+            .flush()?;
+        Ok(())
+    }
+}
+
+impl Storage for StorageSled {
+    fn put(&self, key: &[u8], value: Vec<u8>) -> Result<(), Box<dyn std::error::Error>> {
+        self.db.insert(key, value)?;
+        Ok(())
+    }
+
+    fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, Box<dyn std::error::Error>> {
+        Ok(self.db.get(key)?.map(|v| v.to_vec()))
+    }
+
+    fn scan_prefix(&self, prefix: &[u8]) -> Result<Vec<Vec<u8>>, Box<dyn std::error::Error>> {
+        self.db
+            .scan_prefix(prefix)
+            .map(|result| result.map(|(_, v)| v.to_vec()).map_err(Into::into))
+            .collect()
+    }
+
+    fn flush(&self) -> Result<(), Box<dyn std::error::Error>> {
+        self.db.flush()?;
+        Ok(())
+    }
+}
+
+/// An in-memory `Storage` backend, ordered so `scan_prefix` behaves like
+/// sled's. Meant for unit/integration tests and ephemeral nodes that don't
+/// need the local cache to survive a restart — no temp directory or real
+/// sled database required.
+#[derive(Default)]
+pub struct StorageMemory {
+    data: RwLock<BTreeMap<Vec<u8>, Vec<u8>>>,
+}
+
+impl StorageMemory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Storage for StorageMemory {
+    fn put(&self, key: &[u8], value: Vec<u8>) -> Result<(), Box<dyn std::error::Error>> {
+        self.data.write().unwrap().insert(key.to_vec(), value);
+        Ok(())
+    }
+
+    fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, Box<dyn std::error::Error>> {
+        Ok(self.data.read().unwrap().get(key).cloned())
+    }
+
+    fn scan_prefix(&self, prefix: &[u8]) -> Result<Vec<Vec<u8>>, Box<dyn std::error::Error>> {
+        Ok(self
+            .data
+            .read()
+            .unwrap()
+            .range(prefix.to_vec()..)
+            .take_while(|(k, _)| k.starts_with(prefix))
+            .map(|(_, v)| v.clone())
+            .collect())
+    }
+
+    fn flush(&self) -> Result<(), Box<dyn std::error::Error>> {
+        Ok(())
+    }
+}
+
 /// Represents an ERC1155 token with its metadata
 #[derive(Encode, Decode, Debug, Clone)]
 pub struct Token {
@@ -20,6 +117,9 @@ pub struct Token {
     pub creator: AccountId,
     pub total_supply: u128,
     pub price_threshold: Option<u128>,  // Price at which to trigger transactions
+    /// Number of base-unit decimals this token is denominated in, used to
+    /// parse/format CLI amounts and prices in human-readable decimal form.
+    pub decimals: u8,
 }
 
 /// Represents a balance entry
@@ -37,6 +137,43 @@ pub struct PriceListener {
     pub target_price: u128,
     pub action: PriceAction,
     pub enabled: bool,
+    /// External price feed to poll for this listener, if any. When set,
+    /// `PriceListenerService::start` polls it instead of relying solely on
+    /// manual `UpdatePrice` commands.
+    pub feed_url: Option<String>,
+    /// Symbol to request from `feed_url` (e.g. "DOT/USD").
+    pub feed_symbol: Option<String>,
+    /// When set, overrides the static `target_price` comparison with a
+    /// market-maker-style rolling quote: `process_price_update` fires once
+    /// the price crosses the quote's ask (for a `Sell` action) or bid (for
+    /// a `Buy` action), then re-anchors the quote around the fill price.
+    /// A `Transfer` action has no fire-gating condition of its own, so a
+    /// configured quote instead bounds it through the submission pipeline's
+    /// `SlippageGuard`: the transfer is only submitted while `current_price`
+    /// is still within `[bid, ask]`.
+    pub quote: Option<PriceQuote>,
+}
+
+/// A reference price plus spread, from which live ask/bid levels are
+/// derived as `current_price` streams in.
+#[derive(Encode, Decode, Debug, Clone)]
+pub struct PriceQuote {
+    pub reference_price: u128,
+    /// Spread around `reference_price`, in basis points (1/100 of a
+    /// percent; 10_000 bps = 100%).
+    pub spread_bps: u16,
+}
+
+impl PriceQuote {
+    /// The price at or above which a `Sell` action fires.
+    pub fn ask(&self) -> u128 {
+        self.reference_price.saturating_mul(10_000 + self.spread_bps as u128) / 10_000
+    }
+
+    /// The price at or below which a `Buy` action fires.
+    pub fn bid(&self) -> u128 {
+        self.reference_price.saturating_mul(10_000u128.saturating_sub(self.spread_bps as u128)) / 10_000
+    }
 }
 
 /// Action to take when price threshold is reached
@@ -47,159 +184,503 @@ pub enum PriceAction {
     Transfer { to: AccountId, amount: u128 },
 }
 
-impl StorageSled {
-    /// Open or create a new SLED database for ERC1155 storage
-    pub fn new(path: &str) -> Result<Self, sled::Error> {
-        let db = sled::open(path)?;
-        Ok(Self { db: Arc::new(db) })
-    }
-    
+/// A single `PriceAction` that made it through the transaction-submission
+/// pipeline and was actually signed and submitted, recorded by the
+/// pipeline's audit-logging layer so a listener's execution history can be
+/// reviewed independently of a chain explorer.
+#[derive(Encode, Decode, Debug, Clone)]
+pub struct AuditEntry {
+    pub token_id: u128,
+    pub action: PriceAction,
+    /// The price that triggered execution.
+    pub price: u128,
+    pub tx_hash: H256,
+}
+
+/// A single block the incremental sync has processed, recorded so the next
+/// sync pass can tell whether the chain still agrees with us at that
+/// height or has reorged out from under us.
+#[derive(Encode, Decode, Debug, Clone, PartialEq, Eq)]
+pub struct SyncedBlock {
+    pub number: u32,
+    pub hash: H256,
+}
+
+/// How many of the most recently synced blocks `sync_from_blockchain`
+/// keeps on hand to detect a reorg. A reorg deeper than this forces a full
+/// re-sync from genesis rather than a cheap rollback — a bound chosen to
+/// match common finality depths rather than retaining the chain's entire
+/// history locally.
+const REORG_WINDOW: usize = 32;
+
+/// Upper bound on how many new blocks a single `sync_from_blockchain` call
+/// processes before returning, so syncing a long-unsynced chain yields
+/// control back to the caller instead of blocking indefinitely.
+pub const MAX_BLOCKS_PER_SYNC: u32 = 50;
+
+/// Rolling window of recently synced blocks, most recent last.
+#[derive(Encode, Decode, Debug, Clone, Default)]
+pub struct SyncState {
+    pub recent: Vec<SyncedBlock>,
+}
+
+/// The typed token/balance/listener API, implemented once for every
+/// `Storage` backend via the blanket impl below.
+pub trait TypedStorage: Storage {
     /// Store a new or updated ERC1155 token
-    pub fn store_token(&self, token: Token) -> Result<(), sled::Error> {
+    fn store_token(&self, token: Token) -> Result<(), Box<dyn std::error::Error>> {
         let key = format!("token:{}", token.id);
-        let encoded = token.encode();
-        self.db.insert(key.as_bytes(), encoded)?;
-        Ok(())
+        self.put(key.as_bytes(), token.encode())
     }
-    
+
     /// Retrieve a token by ID
-    pub fn get_token(&self, token_id: u128) -> Result<Option<Token>, Box<dyn std::error::Error>> {
+    fn get_token(&self, token_id: u128) -> Result<Option<Token>, Box<dyn std::error::Error>> {
         let key = format!("token:{}", token_id);
-        if let Some(data) = self.db.get(key.as_bytes())? {
-            let token = Token::decode(&mut &data[..])?;
-            Ok(Some(token))
-        } else {
-            Ok(None)
+        match self.get(key.as_bytes())? {
+            Some(data) => Ok(Some(Token::decode(&mut &data[..])?)),
+            None => Ok(None),
         }
     }
-    
+
     /// Store a balance entry
-    pub fn update_balance(&self, balance: Balance) -> Result<(), sled::Error> {
+    fn update_balance(&self, balance: Balance) -> Result<(), Box<dyn std::error::Error>> {
         let key = format!("balance:{}:{}", balance.token_id, hex::encode(balance.account.as_ref()));
-        let encoded = balance.encode();
-        self.db.insert(key.as_bytes(), encoded)?;
-        Ok(())
+        self.put(key.as_bytes(), balance.encode())
     }
-    
+
     /// Get a balance for account and token
-    pub fn get_balance(&self, account: &AccountId, token_id: u128) -> Result<u128, Box<dyn std::error::Error>> {
+    fn get_balance(&self, account: &AccountId, token_id: u128) -> Result<u128, Box<dyn std::error::Error>> {
         let key = format!("balance:{}:{}", token_id, hex::encode(account.as_ref()));
-        if let Some(data) = self.db.get(key.as_bytes())? {
-            let balance = Balance::decode(&mut &data[..])?;
-            Ok(balance.amount)
-        } else {
-            Ok(0)
+        match self.get(key.as_bytes())? {
+            Some(data) => Ok(Balance::decode(&mut &data[..])?.amount),
+            None => Ok(0),
         }
     }
-    
+
     /// Create or update a price listener for automatic execution
-    pub fn set_price_listener(&self, listener: PriceListener) -> Result<(), sled::Error> {
+    fn set_price_listener(&self, listener: PriceListener) -> Result<(), Box<dyn std::error::Error>> {
         let key = format!("price_listener:{}", listener.token_id);
-        let encoded = listener.encode();
-        self.db.insert(key.as_bytes(), encoded)?;
-        Ok(())
+        self.put(key.as_bytes(), listener.encode())
     }
-    
-    /// Get all price listeners
-    pub fn get_price_listeners(&self) -> Result<Vec<PriceListener>, Box<dyn std::error::Error>> {
-        let mut listeners = Vec::new();
-        
-        let prefix = b"price_listener:";
-        for result in self.db.scan_prefix(prefix) {
-            let (_, data) = result?;
-            let listener = PriceListener::decode(&mut &data[..])?;
-            listeners.push(listener);
+
+    /// Get the price listener for a single token, if one is configured
+    fn get_price_listener(&self, token_id: u128) -> Result<Option<PriceListener>, Box<dyn std::error::Error>> {
+        let key = format!("price_listener:{}", token_id);
+        match self.get(key.as_bytes())? {
+            Some(data) => Ok(Some(PriceListener::decode(&mut &data[..])?)),
+            None => Ok(None),
         }
-        
-        Ok(listeners)
     }
-    
+
+    /// Get all price listeners
+    fn get_price_listeners(&self) -> Result<Vec<PriceListener>, Box<dyn std::error::Error>> {
+        self.scan_prefix(b"price_listener:")?
+            .into_iter()
+            .map(|data| PriceListener::decode(&mut &data[..]).map_err(Into::into))
+            .collect()
+    }
+
     /// Store the contract address on-chain
-    pub fn store_contract_address(&self, address: AccountId) -> Result<(), sled::Error> {
-        self.db.insert(b"contract_address", address.encode())?;
-        Ok(())
+    fn store_contract_address(&self, address: AccountId) -> Result<(), Box<dyn std::error::Error>> {
+        self.put(b"contract_address", address.encode())
     }
-    
+
     /// Get the stored contract address
-    pub fn get_contract_address(&self) -> Result<Option<AccountId>, Box<dyn std::error::Error>> {
-        if let Some(data) = self.db.get(b"contract_address")? {
-            let address = AccountId::decode(&mut &data[..])?;
-            Ok(Some(address))
-        } else {
-            Ok(None)
+    fn get_contract_address(&self) -> Result<Option<AccountId>, Box<dyn std::error::Error>> {
+        match self.get(b"contract_address")? {
+            Some(data) => Ok(Some(AccountId::decode(&mut &data[..])?)),
+            None => Ok(None),
         }
     }
-    
-    /// Store contract state from the blockchain to local SLED storage
-    pub fn sync_from_blockchain<T: subxt::Config>(
-        &self, 
-        api: &subxt::OnlineClient<T>,
-        contract_address: AccountId
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        // Store the contract address
-        self.store_contract_address(contract_address.clone())?;
-        
-        // Logic to sync token data, balances, etc. from blockchain to SLED
-        // This would call the storage_validator functions and store results locally
-        
-        Ok(())
+
+    /// Get the incremental sync's rolling window of recently processed
+    /// blocks, or the default (empty) state if nothing has synced yet.
+    fn get_sync_state(&self) -> Result<SyncState, Box<dyn std::error::Error>> {
+        match self.get(b"sync_state")? {
+            Some(data) => Ok(SyncState::decode(&mut &data[..])?),
+            None => Ok(SyncState::default()),
+        }
     }
-    
-    /// Process price update and execute automatic transactions if thresholds are met
-    pub fn process_price_update<T: subxt::Config>(
-        &self,
-        api: &subxt::OnlineClient<T>,
-        token_id: u128,
-        current_price: u128,
-        signer: &subxt::tx::PairSigner<T, sp_core::sr25519::Pair>
-    ) -> Result<bool, Box<dyn std::error::Error>> 
-    where
-        T::AccountId: From<[u8; 32]>,
-        <T as subxt::Config>::Address: From<T::AccountId>,
-    {
-        // Get the relevant price listener
-        let key = format!("price_listener:{}", token_id);
-        if let Some(data) = self.db.get(key.as_bytes())? {
-            let listener = PriceListener::decode(&mut &data[..])?;
-            
-            // Check if listener is enabled and price threshold is met
-            if listener.enabled && current_price >= listener.target_price {
-                // Execute the action based on the price listener configuration
-                match listener.action {
-                    PriceAction::Sell { amount, min_price } => {
-                        if current_price >= min_price {
-                            // Execute sell transaction
-                            println!("Executing automatic sell of {} tokens at price {}", amount, current_price);
-                            // Call contract to execute the transaction
-                            return Ok(true);
-                        }
-                    },
-                    PriceAction::Buy { amount, max_price } => {
-                        if current_price <= max_price {
-                            // Execute buy transaction
-                            println!("Executing automatic buy of {} tokens at price {}", amount, current_price);
-                            // Call contract to execute the transaction
-                            return Ok(true);
-                        }
-                    },
-                    PriceAction::Transfer { to, amount } => {
-                        // Execute transfer transaction
-                        println!("Executing automatic transfer of {} tokens to {}", amount, to);
-                        // Call contract to execute the transaction
-                        return Ok(true);
-                    }
+
+    /// Persist the incremental sync's rolling window of recently processed
+    /// blocks.
+    fn set_sync_state(&self, state: &SyncState) -> Result<(), Box<dyn std::error::Error>> {
+        self.put(b"sync_state", state.encode())
+    }
+
+    /// All tokens currently cached locally, for re-checking against chain
+    /// state during a sync pass.
+    fn get_tokens(&self) -> Result<Vec<Token>, Box<dyn std::error::Error>> {
+        self.scan_prefix(b"token:")?
+            .into_iter()
+            .map(|data| Token::decode(&mut &data[..]).map_err(Into::into))
+            .collect()
+    }
+
+    /// All balance entries currently cached locally, for re-checking against
+    /// chain state during a sync pass.
+    fn get_balances(&self) -> Result<Vec<Balance>, Box<dyn std::error::Error>> {
+        self.scan_prefix(b"balance:")?
+            .into_iter()
+            .map(|data| Balance::decode(&mut &data[..]).map_err(Into::into))
+            .collect()
+    }
+
+    /// Record an executed `PriceAction`, keyed so `get_audit_entries` can
+    /// scan a single token's history in submission order.
+    fn record_audit_entry(&self, entry: &AuditEntry) -> Result<(), Box<dyn std::error::Error>> {
+        let key = format!("audit:{}:{}", entry.token_id, hex::encode(entry.tx_hash.as_bytes()));
+        self.put(key.as_bytes(), entry.encode())
+    }
+
+    /// All recorded executions for `token_id`.
+    fn get_audit_entries(&self, token_id: u128) -> Result<Vec<AuditEntry>, Box<dyn std::error::Error>> {
+        self.scan_prefix(format!("audit:{}:", token_id).as_bytes())?
+            .into_iter()
+            .map(|data| AuditEntry::decode(&mut &data[..]).map_err(Into::into))
+            .collect()
+    }
+}
+
+impl<S: Storage + ?Sized> TypedStorage for S {}
+
+/// Walks `state.recent` backward from the tip, comparing each cached block
+/// hash against what the chain reports at that height now, and drops every
+/// entry from the point they first disagree onward. Returns the height to
+/// resume syncing from: one past the last entry that still matches, or
+/// `None` if even the oldest entry in the window no longer matches (a reorg
+/// deeper than `REORG_WINDOW`, which forces a full re-sync from genesis).
+///
+/// A `state` with no recorded blocks yet (first sync) is left untouched and
+/// resumes from `None`, same as an unrecoverable reorg — both cases mean
+/// "start from the beginning".
+async fn reconcile_reorg<T: subxt::Config<Hash = H256>>(
+    api: &subxt::OnlineClient<T>,
+    state: &mut SyncState,
+) -> Result<Option<u32>, Box<dyn std::error::Error>> {
+    while let Some(block) = state.recent.last() {
+        let current_hash = api.rpc().block_hash(Some(block.number.into())).await?;
+        if current_hash == Some(block.hash) {
+            return Ok(Some(block.number + 1));
+        }
+        // This height was reorged out from under us; it and everything
+        // synced after it (newer entries, already popped in prior
+        // iterations) are no longer part of the canonical chain.
+        state.recent.pop();
+    }
+    Ok(None)
+}
+
+/// Re-checks every locally cached token URI and balance against current
+/// chain state, correcting any that have drifted. Only meaningful when a
+/// `StorageLayout` is available to derive storage keys from, since deriving
+/// a contract's storage keys requires knowing its field layout.
+async fn refresh_cached_contract_state<S: TypedStorage, T: subxt::Config>(
+    storage: &S,
+    api: &subxt::OnlineClient<T>,
+    contract_address: AccountId,
+    layout: &StorageLayout,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let backend = SubxtBackend::new(api);
+
+    for token in storage.get_tokens()? {
+        let uri = storage_validator::verify_token_uri(&backend, layout, contract_address, token.id).await?;
+        if uri != token.uri {
+            storage.store_token(Token { uri, ..token })?;
+        }
+    }
+
+    for balance in storage.get_balances()? {
+        // `Ok(None)` means the account holds nothing on chain.
+        let amount = storage_validator::verify_token_balance(
+            &backend,
+            layout,
+            contract_address,
+            balance.account,
+            balance.token_id,
+            DEFAULT_DECIMALS,
+        )
+        .await?
+        .unwrap_or(0);
+        if amount != balance.amount {
+            storage.update_balance(Balance { amount, ..balance })?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Incrementally syncs local storage with on-chain state: advances from the
+/// last synced block up to the chain tip (bounded by `MAX_BLOCKS_PER_SYNC`
+/// per call so a long-unsynced chain doesn't block the caller indefinitely),
+/// detecting and unwinding reorgs against the rolling window kept in
+/// `SyncState` along the way.
+///
+/// `layout` enables refreshing locally cached token/balance records against
+/// live chain state once the new tip is reached; without it (e.g. no
+/// contract metadata is available to the caller), sync only tracks block
+/// height and reorg state and logs that cached records were left untouched.
+/// Returns the number of new blocks processed.
+pub async fn sync_from_blockchain<S: TypedStorage, T: subxt::Config<Hash = H256>>(
+    storage: &S,
+    api: &subxt::OnlineClient<T>,
+    contract_address: AccountId,
+    layout: Option<&StorageLayout>,
+) -> Result<u32, Box<dyn std::error::Error>> {
+    storage.store_contract_address(contract_address)?;
+
+    let mut state = storage.get_sync_state()?;
+    let resume_from = reconcile_reorg(api, &mut state).await?;
+
+    let tip_number = match api.rpc().header(None).await? {
+        Some(header) => header.number,
+        None => return Ok(0),
+    };
+
+    let start = resume_from.unwrap_or(0);
+    let end = tip_number.min(start.saturating_add(MAX_BLOCKS_PER_SYNC));
+
+    let mut synced = 0;
+    for number in start..end {
+        let Some(hash) = api.rpc().block_hash(Some(number.into())).await? else {
+            break;
+        };
+        state.recent.push(SyncedBlock { number, hash });
+        if state.recent.len() > REORG_WINDOW {
+            state.recent.remove(0);
+        }
+        synced += 1;
+    }
+
+    storage.set_sync_state(&state)?;
+
+    match layout {
+        Some(layout) if synced > 0 || resume_from.is_none() => {
+            refresh_cached_contract_state(storage, api, contract_address, layout).await?;
+        }
+        Some(_) => {}
+        None => {
+            if synced > 0 {
+                println!(
+                    "Synced {} block(s); no storage layout supplied, so cached token/balance records were left unrefreshed",
+                    synced
+                );
+            }
+        }
+    }
+
+    Ok(synced)
+}
+
+/// Gas limit used for the automatically-submitted transfer below. Fixed
+/// rather than dry-run-estimated (unlike the CLI's interactive transfer
+/// commands), since a dry run would add a round-trip per tick on top of
+/// the submission itself; `transfer_batch`'s bulk submission makes the
+/// same tradeoff.
+const AUTOMATED_TRANSFER_GAS_LIMIT: u64 = 10_000_000_000;
+
+/// Process price update and execute automatic transactions if thresholds are met.
+///
+/// `nonces` reserves the signer's next nonce for whichever action ends
+/// up firing, so that automatic sells/buys triggered back-to-back by a
+/// burst of ticks get sequential nonces from one shared counter instead
+/// of each independently reading (and racing on) the same on-chain
+/// value.
+///
+/// `contract` carries the metadata and address needed to encode and
+/// submit a real contract call for a `Transfer` action through the
+/// `tx_pipeline` stack. Without it (e.g. `PriceListenerService` has no
+/// metadata path configured), `Transfer` falls back to reserving a nonce
+/// and logging what it would have submitted, same as `Sell`/`Buy` always
+/// do — those two have no corresponding contract message to call (there's
+/// no on-chain counterparty for an automated sell/buy), so they remain
+/// simulated regardless of `contract`; they still get an `AuditEntry`
+/// recorded (keyed by the reserved nonce, since there's no real `tx_hash`)
+/// so a listener's full fill history is queryable the same way regardless
+/// of action kind.
+pub async fn process_price_update<S: TypedStorage, T: subxt::Config<Hash = H256>>(
+    storage: &S,
+    api: &subxt::OnlineClient<T>,
+    token_id: u128,
+    current_price: u128,
+    signer: &subxt::tx::PairSigner<T, sp_core::sr25519::Pair>,
+    nonces: &NonceManager,
+    contract: Option<(&Transcoder, AccountId)>,
+) -> Result<bool, Box<dyn std::error::Error>>
+where
+    T::AccountId: Clone + From<[u8; 32]>,
+    <T as subxt::Config>::Address: From<T::AccountId>,
+{
+    // Get the relevant price listener
+    let mut listener = match storage.get_price_listener(token_id)? {
+        Some(listener) => listener,
+        None => return Ok(false),
+    };
+
+    if !listener.enabled {
+        return Ok(false);
+    }
+
+    // With no rolling quote configured, fall back to the original
+    // static-threshold gate: nothing fires until the price has reached
+    // `target_price` at all, regardless of which action is configured.
+    if listener.quote.is_none() && current_price < listener.target_price {
+        return Ok(false);
+    }
+
+    let decimals = storage
+        .get_token(token_id)?
+        .map(|t| t.decimals)
+        .unwrap_or(DEFAULT_DECIMALS);
+
+    // Execute the action based on the price listener configuration
+    match listener.action {
+        PriceAction::Sell { amount, min_price } => {
+            // A quote's ask takes over from the static `min_price` once
+            // configured, so the listener keeps re-firing as the market
+            // moves instead of only once at a fixed level.
+            let fires = match &listener.quote {
+                Some(quote) => current_price >= quote.ask(),
+                None => current_price >= min_price,
+            };
+            if fires {
+                let account = T::AccountId::from(signer.account_id().0);
+                let nonce = nonces.next(api, &account).await?;
+                // Execute sell transaction
+                println!(
+                    "Executing automatic sell of {} tokens at price {} (nonce {})",
+                    format_amount(amount, decimals),
+                    format_amount(current_price, decimals),
+                    nonce
+                );
+                // There's no on-chain counterparty for an automated sell (no
+                // AMM/market contract call exists to make), so this stays
+                // simulated; still record it in the audit trail, keyed by
+                // the reserved nonce since there's no real tx_hash.
+                storage.record_audit_entry(&AuditEntry {
+                    token_id,
+                    action: listener.action.clone(),
+                    price: current_price,
+                    tx_hash: H256::from_low_u64_be(nonce),
+                })?;
+                reanchor_and_store(storage, &mut listener, current_price)?;
+                return Ok(true);
+            }
+        },
+        PriceAction::Buy { amount, max_price } => {
+            let fires = match &listener.quote {
+                Some(quote) => current_price <= quote.bid(),
+                None => current_price <= max_price,
+            };
+            if fires {
+                let account = T::AccountId::from(signer.account_id().0);
+                let nonce = nonces.next(api, &account).await?;
+                // Execute buy transaction
+                println!(
+                    "Executing automatic buy of {} tokens at price {} (nonce {})",
+                    format_amount(amount, decimals),
+                    format_amount(current_price, decimals),
+                    nonce
+                );
+                // Same reasoning as Sell above: no contract call to make, so
+                // just record the simulated fill in the audit trail.
+                storage.record_audit_entry(&AuditEntry {
+                    token_id,
+                    action: listener.action.clone(),
+                    price: current_price,
+                    tx_hash: H256::from_low_u64_be(nonce),
+                })?;
+                reanchor_and_store(storage, &mut listener, current_price)?;
+                return Ok(true);
+            }
+        },
+        PriceAction::Transfer { to, amount } => {
+            match contract {
+                Some((transcoder, contract_address)) => {
+                    let from = AccountId::from(signer.account_id().0);
+                    let message = transcoder.encode_call(
+                        "safe_transfer_from",
+                        vec![
+                            TranscoderValue::AccountId(from),
+                            TranscoderValue::AccountId(to),
+                            TranscoderValue::U128(token_id),
+                            TranscoderValue::U128(amount),
+                            TranscoderValue::Bytes(Vec::new()),
+                        ],
+                    )?;
+
+                    let contract_call_tx = crate::substrate::tx().contracts().call(
+                        T::AccountId::from(contract_address.0),
+                        0u128,
+                        AUTOMATED_TRANSFER_GAS_LIMIT,
+                        None,
+                        message,
+                    );
+
+                    // A Transfer has no fire-gating price check of its own
+                    // (unlike Sell/Buy above), so when a quote is configured
+                    // bound it here instead: only submit while current_price
+                    // hasn't drifted outside the quote's spread.
+                    let within_bounds = match &listener.quote {
+                        Some(quote) => current_price >= quote.bid() && current_price <= quote.ask(),
+                        None => true,
+                    };
+
+                    let submitter = FeeEstimatingSubmitter::new(nonces, 0);
+                    let guarded = SlippageGuard::new(submitter, within_bounds);
+                    let retried = RetryLayer::new(guarded, 3);
+                    let logged = AuditLogger::new(
+                        retried,
+                        storage,
+                        token_id,
+                        listener.action.clone(),
+                        current_price,
+                    );
+
+                    let tx_hash = logged.send(api, signer, &contract_call_tx).await?;
+                    println!(
+                        "Submitted automatic transfer of {} tokens to {} (tx {:?})",
+                        format_amount(amount, decimals),
+                        to,
+                        tx_hash
+                    );
+                }
+                None => {
+                    let account = T::AccountId::from(signer.account_id().0);
+                    let nonce = nonces.next(api, &account).await?;
+                    println!(
+                        "Executing automatic transfer of {} tokens to {} (nonce {})",
+                        format_amount(amount, decimals),
+                        to,
+                        nonce
+                    );
+                    storage.record_audit_entry(&AuditEntry {
+                        token_id,
+                        action: listener.action.clone(),
+                        price: current_price,
+                        tx_hash: H256::from_low_u64_be(nonce),
+                    })?;
                 }
             }
+            return Ok(true);
         }
-        
-        Ok(false)
     }
-    
-    /// Closes the database
-    pub fn close(self) -> Result<(), sled::Error> {
-        Arc::try_unwrap(self.db)
-            .expect("There are other references to the database")
-            .flush()?;
-        Ok(())
+
+    Ok(false)
+}
+
+/// Re-anchors a listener's rolling quote around the price it just fired at,
+/// so the next ask/bid pair is derived from the fill rather than the
+/// original reference price, and persists the updated listener. A no-op
+/// when the listener has no quote (static-threshold listeners don't move).
+fn reanchor_and_store<S: TypedStorage>(
+    storage: &S,
+    listener: &mut PriceListener,
+    fill_price: u128,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(quote) = &mut listener.quote {
+        quote.reference_price = fill_price;
+        storage.set_price_listener(listener.clone())?;
     }
-} 
\ No newline at end of file
+    Ok(())
+}
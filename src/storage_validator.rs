@@ -4,7 +4,6 @@ use subxt::{
     storage::Storage,
     error::Error,
     utils::{AccountId32, H256},
-    PolkadotConfig
 };
 use hex;
 use ink::env::AccountId;
@@ -12,32 +11,210 @@ use sp_core::H160;
 use sp_core::{sr25519, crypto::Ss58Codec};
 use codec::{Encode, Decode};
 use std::collections::HashMap;
+use sp_runtime::traits::BlakeTwo256;
+use sp_trie::{StorageProof, TrieDBBuilder};
+use trie_db::Trie;
+
+use crate::denomination::format_amount;
+use crate::transcoder::{FieldLayout, StorageLayout, Transcoder};
+
+/// Trie layout matching Substrate's state trie: a base-16 Patricia Merkle
+/// trie whose nodes are SCALE-encoded and addressed by their blake2-256
+/// hash.
+type StateTrieLayout = sp_trie::LayoutV1<BlakeTwo256>;
+
+/// Default number of storage reads issued concurrently per wave when
+/// enumerating a contract's tokens and balances. `display_contract_state`
+/// accepts its own batch size, so this only matters to callers that don't
+/// have an opinion.
+pub const PARALLEL_QUERY_BATCH_SIZE: usize = 16;
+
+/// Returned when a `state_getReadProof` response does not reconstruct to
+/// the queried block's `state_root`, as opposed to an ordinary RPC/decode
+/// failure. Distinguishing this from a ordinary error matters: it means the
+/// endpoint served a value that a light client could not have trusted.
+#[derive(Debug)]
+pub struct ProofVerificationFailed;
+
+impl std::fmt::Display for ProofVerificationFailed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "storage proof did not reconstruct to the block's state root")
+    }
+}
+
+impl std::error::Error for ProofVerificationFailed {}
+
+/// Verified storage read: fetches the Merkle inclusion proof for `key` at
+/// `at_block` via `state_getReadProof`, rebuilds an in-memory trie node
+/// database from the proof, and performs a trie lookup rooted at the
+/// block's `state_root` instead of trusting whatever the RPC endpoint
+/// returns, mirroring the proof-backed `get_storage_at` approach used by
+/// light clients like Helios. An absence proof (the key resolves to an
+/// empty branch) is returned as a verified `Ok(None)`; a proof that fails
+/// to reconstruct to `state_root` returns `ProofVerificationFailed`.
+pub async fn verify_storage_proved<T: subxt::Config>(
+    api: &OnlineClient<T>,
+    key: &H256,
+    at_block: H256,
+) -> Result<Option<Vec<u8>>, Box<dyn std::error::Error>> {
+    let key_hex = format!("0x{}", hex::encode(key.as_bytes()));
+
+    let read_proof = api
+        .rpc()
+        .read_proof(vec![key_hex], Some(at_block))
+        .await?;
+
+    let header = api
+        .rpc()
+        .header(Some(at_block))
+        .await?
+        .ok_or(ProofVerificationFailed)?;
+    let state_root = *header.state_root();
+
+    let proof = StorageProof::new(read_proof.proof.into_iter().map(|bytes| bytes.0));
+    let db = proof.into_memory_db::<BlakeTwo256>();
+
+    let trie = TrieDBBuilder::<StateTrieLayout>::new(&db, &state_root).build();
+    let value = trie
+        .get(key.as_bytes())
+        .map_err(|_| ProofVerificationFailed)?
+        .map(|v| v.to_vec());
+
+    Ok(value)
+}
 
 /// This module provides utilities to verify contract storage on the blockchain.
 
+/// Abstracts a single storage read behind a trait, so the verifier
+/// functions below can run against a live chain, a local snapshot, or a
+/// mock in tests instead of being hard-wired to a concrete `OnlineClient`.
+pub trait StorageBackend {
+    /// Reads the raw bytes at `key`, at the given block (`None` means the
+    /// latest block), or `None` if the key doesn't exist.
+    async fn read(
+        &self,
+        key: &[u8],
+        at: Option<H256>,
+    ) -> Result<Option<Vec<u8>>, Box<dyn std::error::Error>>;
+}
+
+/// Live `StorageBackend` wrapping a `subxt` `OnlineClient`.
+pub struct SubxtBackend<'a, T: subxt::Config> {
+    api: &'a OnlineClient<T>,
+}
+
+impl<'a, T: subxt::Config> SubxtBackend<'a, T> {
+    pub fn new(api: &'a OnlineClient<T>) -> Self {
+        Self { api }
+    }
+}
+
+impl<'a, T: subxt::Config> StorageBackend for SubxtBackend<'a, T> {
+    async fn read(
+        &self,
+        key: &[u8],
+        at: Option<H256>,
+    ) -> Result<Option<Vec<u8>>, Box<dyn std::error::Error>> {
+        let key_hex = format!("0x{}", hex::encode(key));
+        let value = self.api.rpc().storage(&key_hex, at).await?;
+        Ok(value.map(|v| v.0))
+    }
+}
+
+/// Proof-verified `StorageBackend`: every read is checked against the
+/// queried block's `state_root` via `verify_storage_proved` instead of
+/// trusting whatever the RPC endpoint returns, the way `SubxtBackend` does.
+/// `None` resolves to the chain's current tip before fetching the proof,
+/// since a proof is always anchored to a specific block.
+pub struct ProvedBackend<'a, T: subxt::Config> {
+    api: &'a OnlineClient<T>,
+}
+
+impl<'a, T: subxt::Config> ProvedBackend<'a, T> {
+    pub fn new(api: &'a OnlineClient<T>) -> Self {
+        Self { api }
+    }
+}
+
+impl<'a, T: subxt::Config<Hash = H256>> StorageBackend for ProvedBackend<'a, T> {
+    async fn read(
+        &self,
+        key: &[u8],
+        at: Option<H256>,
+    ) -> Result<Option<Vec<u8>>, Box<dyn std::error::Error>> {
+        let at_block = match at {
+            Some(hash) => hash,
+            None => {
+                let tip_number = self.api.rpc().header(None).await?
+                    .ok_or("chain has no best block")?
+                    .number;
+                self.api.rpc().block_hash(Some(tip_number.into())).await?
+                    .ok_or("chain has no block hash for its own tip")?
+            }
+        };
+
+        let mut key_bytes = [0u8; 32];
+        if key.len() != key_bytes.len() {
+            return Err("storage key is not 32 bytes, can't be proof-verified as an H256".into());
+        }
+        key_bytes.copy_from_slice(key);
+
+        verify_storage_proved(self.api, &H256::from(key_bytes), at_block).await
+    }
+}
+
+/// In-memory `StorageBackend` keyed by raw storage key bytes, for unit
+/// tests and offline inspection of exported state dumps where no live
+/// node is available.
+#[derive(Debug, Default, Clone)]
+pub struct MemoryBackend {
+    entries: HashMap<Vec<u8>, Vec<u8>>,
+}
+
+impl MemoryBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Loads a key/value pair as if it had been read from a live chain.
+    pub fn insert(&mut self, key: Vec<u8>, value: Vec<u8>) {
+        self.entries.insert(key, value);
+    }
+}
+
+impl StorageBackend for MemoryBackend {
+    async fn read(
+        &self,
+        key: &[u8],
+        _at: Option<H256>,
+    ) -> Result<Option<Vec<u8>>, Box<dyn std::error::Error>> {
+        Ok(self.entries.get(key).cloned())
+    }
+}
+
 /// Fetches and displays contract state
-pub async fn display_contract_state<T: subxt::Config>(
-    api: &OnlineClient<T>,
+pub async fn display_contract_state(
+    backend: &impl StorageBackend,
+    layout: &StorageLayout,
     contract_address: AccountId,
     block_number: u32,
+    batch_size: usize,
+    decimals: u8,
 ) -> Result<(), Box<dyn std::error::Error>> {
     println!("\n=== Contract State Verification ===");
     println!("Contract Address: {}", contract_address);
-    
+    println!("Storage Layout Version: {}", layout.version);
+
     // Convert ink AccountId to substrate AccountId32
     let contract_account = AccountId32::from(contract_address.0);
     
     // Get the contract code hash
     let code_hash_key = compute_contract_code_hash_key(contract_address);
-    let code_hash_key_hex = format!("0x{}", hex::encode(code_hash_key.as_bytes()));
-    
-    let code_hash_value = api.rpc().storage(
-        &code_hash_key_hex,
-        None
-    ).await?;
-    
+
+    let code_hash_value = backend.read(code_hash_key.as_bytes(), None).await?;
+
     if let Some(value) = code_hash_value {
-        println!("Contract Code Hash: 0x{}", hex::encode(&value.0));
+        println!("Contract Code Hash: 0x{}", hex::encode(&value));
     } else {
         println!("Contract code hash not found - contract may not exist");
         return Ok(());
@@ -51,25 +228,21 @@ pub async fn display_contract_state<T: subxt::Config>(
     println!("4. Token ID Nonce: u128");
     
     // Fetch the token ID nonce to know how many tokens exist
-    let nonce_key = compute_nonce_storage_key(contract_address);
-    let nonce_key_hex = format!("0x{}", hex::encode(nonce_key.as_bytes()));
-    
-    let nonce_value = api.rpc().storage(
-        &nonce_key_hex,
-        None
-    ).await?;
-    
+    let nonce_key = compute_nonce_storage_key(layout, contract_address)?;
+
+    let nonce_value = backend.read(nonce_key.as_bytes(), None).await?;
+
     let mut token_count = 0;
-    
+
     if let Some(value) = nonce_value {
-        if !value.0.is_empty() {
+        if !value.is_empty() {
             // Decode the nonce value
-            token_count = match u128::decode(&mut &value.0[..]) {
+            token_count = match u128::decode(&mut &value[..]) {
                 Ok(n) => n,
                 Err(_) => {
-                    if value.0.len() >= 16 {
+                    if value.len() >= 16 {
                         let mut nonce_bytes = [0u8; 16];
-                        nonce_bytes.copy_from_slice(&value.0[0..16]);
+                        nonce_bytes.copy_from_slice(&value[0..16]);
                         u128::from_le_bytes(nonce_bytes)
                     } else {
                         0
@@ -84,55 +257,82 @@ pub async fn display_contract_state<T: subxt::Config>(
     }
     
     println!("\n--- Token Data ---");
-    // Query all tokens up to the nonce value
+    // Query all tokens up to the nonce value, fetching in concurrent waves
+    // instead of one awaited round-trip per token.
     let mut token_data = HashMap::new();
-    
-    for token_id in 1..=token_count {
-        // Get URI for each token
-        match verify_token_uri(api, contract_address.clone(), token_id).await {
-            Ok(uri) => {
+    let token_ids: Vec<u128> = (1..=token_count).collect();
+
+    for chunk in token_ids.chunks(batch_size.max(1)) {
+        let uris = futures::future::join_all(
+            chunk.iter().map(|&token_id| verify_token_uri(backend, layout, contract_address.clone(), token_id)),
+        )
+        .await;
+
+        for (&token_id, uri) in chunk.iter().zip(uris) {
+            if let Ok(uri) = uri {
                 if !uri.is_empty() {
                     token_data.insert(token_id, uri);
                 }
-            },
-            Err(_) => {}
+            }
         }
     }
-    
+
     for (id, uri) in token_data {
         println!("Token #{}: URI = {}", id, uri);
     }
-    
+
     println!("\n--- Balance Data ---");
     println!("Scanning for non-zero balances in the tokens...");
-    
+
     // Known test accounts to check (user can extend this based on their usage)
     let test_accounts = vec![
         contract_address.clone(), // Contract itself
         AccountId::from([0; 32]), // Zero address
         // Additional accounts could be added from command line or configuration
     ];
-    
+
     let mut found_balances = false;
-    
-    // Check balances for each token and known account
-    for token_id in 1..=token_count {
-        for account in &test_accounts {
-            match verify_token_balance(api, contract_address.clone(), account.clone(), token_id).await {
-                Ok(balance) => {
+
+    // Check balances for each (token, account) pair, again fetched in
+    // concurrent waves of `batch_size` rather than sequentially - this is
+    // what turns an O(tokens * accounts) scan from minutes into a handful
+    // of round-trips.
+    let balance_queries: Vec<(u128, AccountId)> = token_ids
+        .iter()
+        .flat_map(|&token_id| test_accounts.iter().map(move |account| (token_id, account.clone())))
+        .collect();
+
+    for chunk in balance_queries.chunks(batch_size.max(1)) {
+        let balances = futures::future::join_all(
+            chunk
+                .iter()
+                .map(|(token_id, account)| verify_token_balance(backend, layout, contract_address.clone(), account.clone(), *token_id, decimals)),
+        )
+        .await;
+
+        for ((token_id, account), result) in chunk.iter().zip(balances) {
+            match result {
+                Ok(Some(balance)) => {
                     if balance > 0 {
                         found_balances = true;
-                        println!("Account {} has {} of token #{}", 
-                                account, balance, token_id);
+                        println!("Account {} has {} ({}) of token #{}",
+                                account, balance, format_amount(balance, decimals), token_id);
                     }
                 },
+                Ok(None) => {}
+                Err(StorageError::DecodeFailed { raw, expected }) => {
+                    println!(
+                        "Warning: balance for account {} / token #{} held undecodable bytes (expected {}): 0x{}",
+                        account, token_id, expected, hex::encode(&raw)
+                    );
+                }
                 Err(e) => {
                     println!("Error checking balance: {}", e);
                 }
             }
         }
     }
-    
+
     if !found_balances {
         println!("No non-zero balances found in test accounts. This doesn't mean there are no balances at all.");
         println!("To check specific accounts, use the 'balance' command.");
@@ -143,59 +343,166 @@ pub async fn display_contract_state<T: subxt::Config>(
     Ok(())
 }
 
-/// Attempts to verify a token balance directly from contract storage
-pub async fn verify_token_balance<T: subxt::Config>(
+/// Dumps every field of the contract's `#[ink(storage)]` struct as
+/// described by the metadata's storage layout, instead of only special-
+/// casing the ERC1155 balances/URIs/nonce fields. Cell fields are read
+/// directly; `Mapping` fields are reported by their root key since their
+/// entries aren't enumerable without a specific map key. A `block_number`
+/// of 0 reads the latest block.
+pub async fn dump_storage<T: subxt::Config>(
     api: &OnlineClient<T>,
+    transcoder: &Transcoder,
+    contract_address: AccountId,
+    block_number: u32,
+) -> Result<(), Box<dyn std::error::Error>> {
+    println!("\n=== Contract Storage Dump ===");
+    println!("Contract Address: {}", contract_address);
+
+    let at = match block_number {
+        0 => None,
+        n => Some(
+            api.rpc()
+                .block_hash(Some(n.into()))
+                .await?
+                .ok_or("no block at the requested block number")?,
+        ),
+    };
+
+    let fields = transcoder.storage_fields();
+    if fields.is_empty() {
+        println!("No storage layout found in contract metadata");
+        return Ok(());
+    }
+
+    for field in fields {
+        match &field.layout {
+            FieldLayout::Cell { key } => {
+                let storage_key = compute_cell_storage_key(contract_address.clone(), key);
+                let storage_key_hex = format!("0x{}", hex::encode(storage_key.as_bytes()));
+                let value = api.rpc().storage(&storage_key_hex, at).await?;
+
+                match value {
+                    Some(v) if !v.0.is_empty() => {
+                        println!("{}: 0x{}", field.name, hex::encode(&v.0))
+                    }
+                    _ => println!("{}: <empty>", field.name),
+                }
+            }
+            FieldLayout::Mapping { root_key } => {
+                println!(
+                    "{}: Mapping (root_key=0x{}) - entries require a specific key, not enumerable via a flat dump",
+                    field.name,
+                    hex::encode(root_key)
+                );
+            }
+        }
+    }
+
+    println!("=== End of Storage Dump ===");
+    Ok(())
+}
+
+/// Distinguishes a genuinely absent storage key from one that existed but
+/// failed to decode into the expected type, so the two can't silently
+/// collapse into the same zero/empty result the way `Ok(0)` used to.
+/// Mirrors the trie/state-corrupt errors OpenEthereum propagates instead of
+/// treating storage corruption as a zero balance.
+#[derive(Debug)]
+pub enum StorageError {
+    /// The storage key does not exist (or holds no bytes).
+    KeyAbsent,
+    /// The key held bytes, but they didn't decode as `expected`.
+    DecodeFailed { raw: Vec<u8>, expected: &'static str },
+    /// The backend read itself failed (RPC/transport error).
+    Rpc(Box<dyn std::error::Error>),
+    /// The contract's storage layout doesn't describe a field this
+    /// function needs, e.g. because the deployed contract's metadata
+    /// doesn't match the ERC1155 shape this CLI expects.
+    LayoutFieldMissing(&'static str),
+}
+
+impl std::fmt::Display for StorageError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StorageError::KeyAbsent => write!(f, "storage key not found"),
+            StorageError::DecodeFailed { raw, expected } => write!(
+                f,
+                "failed to decode {} byte(s) as {}: 0x{}",
+                raw.len(),
+                expected,
+                hex::encode(raw)
+            ),
+            StorageError::Rpc(e) => write!(f, "storage read failed: {}", e),
+            StorageError::LayoutFieldMissing(field) => {
+                write!(f, "contract storage layout has no field named '{}'", field)
+            }
+        }
+    }
+}
+
+impl std::error::Error for StorageError {}
+
+/// Attempts to verify a token balance directly from contract storage.
+/// Returns `Ok(None)` when the account genuinely holds nothing, as opposed
+/// to `Err(StorageError::DecodeFailed)` when the key held bytes that
+/// couldn't be decoded as a balance.
+pub async fn verify_token_balance(
+    backend: &impl StorageBackend,
+    layout: &StorageLayout,
     contract_address: AccountId,
     account: AccountId,
     token_id: u128,
-) -> Result<u128, Box<dyn std::error::Error>> {
+    decimals: u8,
+) -> Result<Option<u128>, StorageError> {
     // Convert ink AccountId to substrate AccountId32
-    let contract_account = AccountId32::from(contract_address.0);
     let user_account = AccountId32::from(account.0);
-    
-    println!("Verifying balance from storage for account {} and token {}", 
+
+    println!("Verifying balance from storage for account {} and token {}",
              hex::encode(user_account.as_ref()),
              token_id);
-    
+
     // Compute the storage key for the balances mapping
-    let storage_key = compute_balance_storage_key(contract_address, account, token_id);
-    
-    // Convert to hex string for API call
-    let storage_key_hex = format!("0x{}", hex::encode(storage_key.as_bytes()));
-    
+    let storage_key = compute_balance_storage_key(layout, contract_address, account, token_id)?;
+
     // Try to get the storage value
-    let storage_value = api.rpc().storage(
-        &storage_key_hex,
-        None
-    ).await?;
-    
-    if let Some(value) = storage_value {
-        // Decode the balance
-        if !value.0.is_empty() {
-            // For ink! contracts, the storage value is typically SCALE encoded
-            let balance = match u128::decode(&mut &value.0[..]) {
-                Ok(b) => b,
-                Err(_) => {
-                    // If direct decoding fails, try parsing as bytes
-                    if value.0.len() >= 16 {
-                        let mut balance_bytes = [0u8; 16];
-                        balance_bytes.copy_from_slice(&value.0[0..16]);
-                        u128::from_le_bytes(balance_bytes)
-                    } else {
-                        0
-                    }
-                }
-            };
-            
-            println!("Successfully decoded balance: {}", balance);
-            return Ok(balance);
+    let storage_value = backend
+        .read(storage_key.as_bytes(), None)
+        .await
+        .map_err(StorageError::Rpc)?;
+
+    let value = match storage_value {
+        Some(value) if !value.is_empty() => value,
+        _ => {
+            println!("No balance found in storage for this account/token");
+            return Ok(None);
         }
-    }
-    
-    // If not found or couldn't decode, return 0
-    println!("Could not find balance in storage, returning 0");
-    Ok(0)
+    };
+
+    // For ink! contracts, the storage value is typically SCALE encoded
+    let balance = match u128::decode(&mut &value[..]) {
+        Ok(b) => b,
+        Err(_) => {
+            // If direct decoding fails, try parsing as raw little-endian bytes
+            if value.len() >= 16 {
+                let mut balance_bytes = [0u8; 16];
+                balance_bytes.copy_from_slice(&value[0..16]);
+                u128::from_le_bytes(balance_bytes)
+            } else {
+                return Err(StorageError::DecodeFailed {
+                    raw: value,
+                    expected: "u128",
+                });
+            }
+        }
+    };
+
+    println!(
+        "Successfully decoded balance: {} ({} at {} decimals)",
+        balance,
+        format_amount(balance, decimals),
+        decimals
+    );
+    Ok(Some(balance))
 }
 
 /// Attempts to identify what a storage item might be based on its key and value
@@ -233,136 +540,168 @@ fn identify_storage_item(key: &str, value: &[u8]) {
 
 /// Function to retrieve and display token URI directly from blockchain storage
 pub async fn verify_token_uri(
-    api: &OnlineClient<PolkadotConfig>,
+    backend: &impl StorageBackend,
+    layout: &StorageLayout,
     contract_address: AccountId,
     token_id: u128,
 ) -> Result<String, Box<dyn std::error::Error>> {
     println!("Verifying token URI directly from blockchain storage...");
-    
+
     // Compute the storage key for the URI mapping
-    let storage_key = compute_uri_storage_key(contract_address, token_id);
-    
-    // Convert to hex string for API call
-    let storage_key_hex = format!("0x{}", hex::encode(storage_key.as_bytes()));
-    
+    let storage_key = compute_uri_storage_key(layout, contract_address, token_id)?;
+
     // Try to get the storage value
-    let storage_value = api.rpc().storage(
-        &storage_key_hex,
-        None
-    ).await?;
-    
+    let storage_value = backend.read(storage_key.as_bytes(), None).await?;
+
     if let Some(value) = storage_value {
         // Decode the URI
-        if !value.0.is_empty() {
+        if !value.is_empty() {
             // Try to decode as a SCALE-encoded string
-            match String::decode(&mut &value.0[..]) {
+            match String::decode(&mut &value[..]) {
                 Ok(uri) => {
                     println!("URI found in blockchain storage: {}", uri);
                     return Ok(uri);
                 },
                 Err(_) => {
                     // If SCALE decoding fails, try UTF-8 decoding
-                    match String::from_utf8(value.0.clone()) {
+                    match String::from_utf8(value.clone()) {
                         Ok(uri) => {
                             println!("URI found (raw UTF-8) in storage: {}", uri);
                             return Ok(uri);
                         },
                         Err(_) => {
-                            println!("Found data but could not decode as string: 0x{}", 
-                                    hex::encode(&value.0));
-                            return Ok(format!("0x{}", hex::encode(&value.0)));
+                            println!("Found data but could not decode as string: 0x{}",
+                                    hex::encode(&value));
+                            return Ok(format!("0x{}", hex::encode(&value)));
                         }
                     }
                 }
             }
         }
     }
-    
+
     println!("No URI found in storage");
     Ok(String::new())
 }
 
-// Actual storage key computation functions that match ink! contract storage layout
+// Storage key computation functions that match ink! contract storage
+// layout. Each field's root key comes from the contract's own metadata
+// (via `StorageLayout`) rather than a hardcoded field-name hash, so these
+// keep working across storage-layout versions instead of silently
+// computing the wrong key for a field that moved or was renamed.
+
+/// Looks up a `Mapping` field's root key in the resolved layout.
+fn mapping_root_key<'a>(layout: &'a StorageLayout, name: &'static str) -> Result<&'a [u8], StorageError> {
+    match layout.field(name) {
+        Some(FieldLayout::Mapping { root_key }) => Ok(root_key.as_slice()),
+        _ => Err(StorageError::LayoutFieldMissing(name)),
+    }
+}
+
+/// Looks up a plain value cell's key in the resolved layout.
+fn cell_key<'a>(layout: &'a StorageLayout, name: &'static str) -> Result<&'a [u8], StorageError> {
+    match layout.field(name) {
+        Some(FieldLayout::Cell { key }) => Ok(key.as_slice()),
+        _ => Err(StorageError::LayoutFieldMissing(name)),
+    }
+}
 
 fn compute_balance_storage_key(
+    layout: &StorageLayout,
     contract_address: AccountId,
     account: AccountId,
     token_id: u128,
-) -> H256 {
+) -> Result<H256, StorageError> {
     // Storage layout for ink! contracts:
     // 1. Contract namespace: blake2_128_concat(contract_address)
-    // 2. Field identifier: twox_128("balances")
+    // 2. Field root key: from the contract's metadata
     // 3. Map key: blake2_128_concat((token_id, account))
-    
+    let root_key = mapping_root_key(layout, "balances")?;
+
     // Step 1: Create the map key
-    let mut token_id_bytes = token_id.encode();
-    let mut account_bytes = account.encode();
     let mut map_key = Vec::new();
-    map_key.append(&mut token_id_bytes);
-    map_key.append(&mut account_bytes);
-    
+    map_key.extend_from_slice(&token_id.encode());
+    map_key.extend_from_slice(&account.encode());
+
     // Compose the full storage key
     let contract_prefix = blake2_128_concat(contract_address.encode().as_slice());
-    let field_identifier = twox_128(b"balances");
     let encoded_map_key = blake2_128_concat(&map_key);
-    
+
     // Combine all parts
     let mut full_key = Vec::new();
     full_key.extend_from_slice(&contract_prefix);
-    full_key.extend_from_slice(&field_identifier);
+    full_key.extend_from_slice(root_key);
     full_key.extend_from_slice(&encoded_map_key);
-    
+
     // Convert to H256 (padded if needed)
     let mut result = [0u8; 32];
     let len = std::cmp::min(full_key.len(), 32);
     result[..len].copy_from_slice(&full_key[..len]);
-    
-    H256::from(result)
+
+    Ok(H256::from(result))
 }
 
 fn compute_uri_storage_key(
+    layout: &StorageLayout,
     contract_address: AccountId,
     token_id: u128,
-) -> H256 {
-    // Storage layout for token URIs, similar to balances but with different field name
-    
+) -> Result<H256, StorageError> {
+    // Storage layout for token URIs, similar to balances but rooted at a
+    // different field.
+    let root_key = mapping_root_key(layout, "token_uris")?;
+
     // Contract namespace
     let contract_prefix = blake2_128_concat(contract_address.encode().as_slice());
-    // Field identifier
-    let field_identifier = twox_128(b"token_uris");
     // Map key
     let encoded_map_key = blake2_128_concat(&token_id.encode());
-    
+
     // Combine all parts
     let mut full_key = Vec::new();
     full_key.extend_from_slice(&contract_prefix);
-    full_key.extend_from_slice(&field_identifier);
+    full_key.extend_from_slice(root_key);
     full_key.extend_from_slice(&encoded_map_key);
-    
+
     // Convert to H256
     let mut result = [0u8; 32];
     let len = std::cmp::min(full_key.len(), 32);
     result[..len].copy_from_slice(&full_key[..len]);
-    
-    H256::from(result)
+
+    Ok(H256::from(result))
 }
 
-fn compute_nonce_storage_key(contract_address: AccountId) -> H256 {
+fn compute_nonce_storage_key(layout: &StorageLayout, contract_address: AccountId) -> Result<H256, StorageError> {
+    let field_key = cell_key(layout, "token_id_nonce")?;
+
     // Contract namespace
     let contract_prefix = blake2_128_concat(contract_address.encode().as_slice());
-    // Field identifier for token_id_nonce
-    let field_identifier = twox_128(b"token_id_nonce");
-    
+
     // Combine parts
     let mut full_key = Vec::new();
     full_key.extend_from_slice(&contract_prefix);
-    full_key.extend_from_slice(&field_identifier);
-    
+    full_key.extend_from_slice(field_key);
+
     // Convert to H256
     let mut result = [0u8; 32];
     let len = std::cmp::min(full_key.len(), 32);
     result[..len].copy_from_slice(&full_key[..len]);
-    
+
+    Ok(H256::from(result))
+}
+
+/// Computes a cell field's storage key from the metadata-derived field key
+/// directly, instead of hashing a hand-picked field name, so `dump_storage`
+/// works for any field the contract metadata describes.
+fn compute_cell_storage_key(contract_address: AccountId, field_key: &[u8]) -> H256 {
+    let contract_prefix = blake2_128_concat(contract_address.encode().as_slice());
+
+    let mut full_key = Vec::new();
+    full_key.extend_from_slice(&contract_prefix);
+    full_key.extend_from_slice(field_key);
+
+    let mut result = [0u8; 32];
+    let len = std::cmp::min(full_key.len(), 32);
+    result[..len].copy_from_slice(&full_key[..len]);
+
     H256::from(result)
 }
 
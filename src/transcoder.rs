@@ -0,0 +1,552 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use codec::{Decode, Encode};
+use ink::env::AccountId;
+use serde::Deserialize;
+use sp_core::blake2_256;
+
+use crate::error::CliError;
+
+/// Minimal mirror of the pieces of the ink! metadata JSON we care about:
+/// the message table (label -> selector/args) and the event table
+/// (label -> field names), both keyed by the human-readable label so the
+/// CLI can work against any deployed ink! contract instead of one with
+/// hand-rolled selectors.
+#[derive(Debug, Deserialize)]
+struct RawMetadata {
+    spec: RawSpec,
+    #[serde(default)]
+    storage: Option<RawStorage>,
+    /// The metadata schema version (ink! metadata's top-level `version`
+    /// field). Older metadata predates this field, so it's optional.
+    #[serde(default)]
+    version: Option<serde_json::Value>,
+}
+
+/// Mirrors the top-level `storage` key of ink! metadata: a `root` layout
+/// node describing the contract's `#[ink(storage)]` struct field-by-field,
+/// used to drive a generic storage dump instead of hand-picking known
+/// ERC1155 fields.
+#[derive(Debug, Deserialize)]
+struct RawStorage {
+    root: RawRootLayout,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawRootLayout {
+    root_key: String,
+    layout: RawLayout,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum RawLayout {
+    Struct(RawStructLayout),
+    Leaf(RawLeafLayout),
+    Root(RawNestedRootLayout),
+    #[serde(other)]
+    Unsupported,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawStructLayout {
+    fields: Vec<RawFieldLayout>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawFieldLayout {
+    name: String,
+    layout: RawLayout,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawLeafLayout {
+    key: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawNestedRootLayout {
+    root_key: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawSpec {
+    messages: Vec<RawMessage>,
+    #[serde(default)]
+    events: Vec<RawEvent>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawMessage {
+    label: String,
+    selector: String,
+    args: Vec<RawArg>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawArg {
+    label: String,
+    #[serde(rename = "type")]
+    ty: RawArgType,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawArgType {
+    #[serde(rename = "displayName")]
+    display_name: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawEvent {
+    label: String,
+    args: Vec<RawArg>,
+}
+
+/// The argument types the transcoder knows how to SCALE-encode from a
+/// plain CLI string. This is deliberately a small subset of ink!'s type
+/// table: enough to cover the primitives ERC1155-shaped contracts use.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArgKind {
+    AccountId,
+    U128,
+    U64,
+    U32,
+    Bool,
+    String,
+    Bytes,
+}
+
+/// A single message argument: its CLI label (for error messages) and its
+/// resolved scalar kind.
+#[derive(Debug, Clone)]
+pub struct ArgSpec {
+    pub label: String,
+    pub kind: ArgKind,
+}
+
+/// A resolved message: the 4-byte selector plus the ordered argument
+/// kinds needed to encode a call.
+#[derive(Debug, Clone)]
+pub struct MessageSpec {
+    pub selector: [u8; 4],
+    pub args: Vec<ArgSpec>,
+}
+
+/// A resolved event: label plus ordered field kinds, used to render
+/// `ContractEmitted` payloads as named fields instead of raw hex.
+#[derive(Debug, Clone)]
+pub struct EventSpec {
+    pub label: String,
+    pub args: Vec<ArgSpec>,
+}
+
+/// A single CLI-supplied argument value, already converted from its
+/// string form into something that can be SCALE-encoded.
+#[derive(Debug, Clone)]
+pub enum TranscoderValue {
+    AccountId(AccountId),
+    U128(u128),
+    U64(u64),
+    U32(u32),
+    Bool(bool),
+    String(String),
+    Bytes(Vec<u8>),
+}
+
+/// A single field of the `#[ink(storage)]` struct, resolved from the
+/// metadata's storage layout. Used to drive a generic storage dump instead
+/// of hand-picking known ERC1155 fields.
+#[derive(Debug, Clone)]
+pub struct StorageField {
+    pub name: String,
+    pub layout: FieldLayout,
+}
+
+/// How a storage field is laid out in the contract's child-trie storage.
+#[derive(Debug, Clone)]
+pub enum FieldLayout {
+    /// A plain value cell at a fixed key.
+    Cell { key: Vec<u8> },
+    /// A `Mapping`/lazy-storage field, rooted at `root_key`. Individual
+    /// entries are keyed by their map key hashed under this root, so they
+    /// aren't enumerable from the layout alone.
+    Mapping { root_key: Vec<u8> },
+}
+
+/// Loads an ink! contract's `metadata.json` and transcodes CLI calls
+/// into selector+SCALE-args blobs by message label, replacing the
+/// hand-rolled `compute_selector`/`params.encode()` pairs that assumed
+/// an Ethereum-style ABI.
+pub struct Transcoder {
+    messages: HashMap<String, MessageSpec>,
+    events: HashMap<String, EventSpec>,
+    storage: Vec<StorageField>,
+    version: String,
+}
+
+/// A contract's storage layout resolved from its own metadata, keyed by
+/// `#[ink(storage)]` field name, so key computation tracks whatever layout
+/// the deployed contract actually has instead of a fixed set of field
+/// names and a single hashing scheme. `version` carries the metadata's
+/// declared schema version alongside the layout, so a single binary can
+/// tell contracts compiled against different ink! versions apart when
+/// interpreting their storage keys.
+#[derive(Debug, Clone)]
+pub struct StorageLayout {
+    pub version: String,
+    fields: HashMap<String, FieldLayout>,
+}
+
+impl StorageLayout {
+    /// Looks up a field's layout by its `#[ink(storage)]` struct name.
+    pub fn field(&self, name: &str) -> Option<&FieldLayout> {
+        self.fields.get(name)
+    }
+}
+
+impl Transcoder {
+    /// Loads and indexes a contract's metadata file by message/event label.
+    pub fn load(metadata_path: &Path) -> Result<Self, CliError> {
+        let raw = fs::read_to_string(metadata_path)
+            .map_err(|_| CliError::MetadataNotFound(metadata_path.display().to_string()))?;
+        let metadata: RawMetadata =
+            serde_json::from_str(&raw).map_err(|_| CliError::InvalidMetadata)?;
+
+        let mut messages = HashMap::new();
+        for message in metadata.spec.messages {
+            let selector = decode_selector(&message.selector)?;
+            let args = message
+                .args
+                .iter()
+                .map(|arg| resolve_arg(arg))
+                .collect::<Result<Vec<_>, _>>()?;
+            messages.insert(message.label.clone(), MessageSpec { selector, args });
+        }
+
+        let mut events = HashMap::new();
+        for event in metadata.spec.events {
+            let args = event
+                .args
+                .iter()
+                .map(|arg| resolve_arg(arg))
+                .collect::<Result<Vec<_>, _>>()?;
+            events.insert(
+                event.label.clone(),
+                EventSpec {
+                    label: event.label.clone(),
+                    args,
+                },
+            );
+        }
+
+        let mut storage = Vec::new();
+        if let Some(raw_storage) = metadata.storage {
+            resolve_layout(String::new(), &raw_storage.root.layout, &mut storage)?;
+        }
+
+        let version = metadata
+            .version
+            .map(|v| v.to_string().trim_matches('"').to_string())
+            .unwrap_or_else(|| "unknown".to_string());
+
+        Ok(Self { messages, events, storage, version })
+    }
+
+    /// Returns the contract's `#[ink(storage)]` fields, resolved from the
+    /// metadata's storage layout, for a generic storage dump.
+    pub fn storage_fields(&self) -> &[StorageField] {
+        &self.storage
+    }
+
+    /// Returns the contract's storage layout keyed by field name, for
+    /// version-aware storage key computation against the actual deployed
+    /// layout rather than hardcoded field names.
+    pub fn storage_layout(&self) -> StorageLayout {
+        StorageLayout {
+            version: self.version.clone(),
+            fields: self
+                .storage
+                .iter()
+                .map(|f| (f.name.clone(), f.layout.clone()))
+                .collect(),
+        }
+    }
+
+    /// Looks up a message by label and returns its selector plus arg kinds.
+    pub fn message(&self, label: &str) -> Result<&MessageSpec, CliError> {
+        self.messages
+            .get(label)
+            .ok_or_else(|| CliError::UnknownMessage(label.to_string()))
+    }
+
+    /// Encodes `selector || SCALE(args)` for the named message, validating
+    /// that the caller supplied the right number of arguments.
+    pub fn encode_call(&self, label: &str, values: Vec<TranscoderValue>) -> Result<Vec<u8>, CliError> {
+        let spec = self.message(label)?;
+        if spec.args.len() != values.len() {
+            return Err(CliError::ArgumentCountMismatch {
+                message: label.to_string(),
+                expected: spec.args.len(),
+                got: values.len(),
+            });
+        }
+
+        let mut out = spec.selector.to_vec();
+        for (arg, value) in spec.args.iter().zip(values.iter()) {
+            encode_value(arg, value, &mut out)?;
+        }
+        Ok(out)
+    }
+
+    /// Looks up an event's field layout by label, for decoding
+    /// `ContractEmitted` payloads into named fields.
+    pub fn event(&self, label: &str) -> Option<&EventSpec> {
+        self.events.get(label)
+    }
+}
+
+fn decode_selector(hex_selector: &str) -> Result<[u8; 4], CliError> {
+    let bytes = hex::decode(hex_selector.trim_start_matches("0x")).map_err(|_| CliError::InvalidMetadata)?;
+    if bytes.len() != 4 {
+        return Err(CliError::InvalidMetadata);
+    }
+    let mut selector = [0u8; 4];
+    selector.copy_from_slice(&bytes);
+    Ok(selector)
+}
+
+fn resolve_arg(arg: &RawArg) -> Result<ArgSpec, CliError> {
+    let kind = match arg.ty.display_name.last().map(String::as_str) {
+        Some("AccountId") => ArgKind::AccountId,
+        Some("u128") => ArgKind::U128,
+        Some("u64") => ArgKind::U64,
+        Some("u32") => ArgKind::U32,
+        Some("bool") => ArgKind::Bool,
+        Some("String") | Some("str") => ArgKind::String,
+        Some("Vec") => ArgKind::Bytes,
+        _ => return Err(CliError::UnsupportedArgType(arg.label.clone())),
+    };
+    Ok(ArgSpec {
+        label: arg.label.clone(),
+        kind,
+    })
+}
+
+/// Flattens a metadata storage layout node into `StorageField`s, recursing
+/// into nested structs and naming fields with a `.`-joined path (e.g.
+/// `lifecycle_state.paused`).
+fn resolve_layout(prefix: String, layout: &RawLayout, out: &mut Vec<StorageField>) -> Result<(), CliError> {
+    match layout {
+        RawLayout::Struct(s) => {
+            for field in &s.fields {
+                let name = if prefix.is_empty() {
+                    field.name.clone()
+                } else {
+                    format!("{}.{}", prefix, field.name)
+                };
+                resolve_layout(name, &field.layout, out)?;
+            }
+            Ok(())
+        }
+        RawLayout::Leaf(leaf) => {
+            let key = hex::decode(leaf.key.trim_start_matches("0x")).map_err(|_| CliError::InvalidMetadata)?;
+            out.push(StorageField {
+                name: prefix,
+                layout: FieldLayout::Cell { key },
+            });
+            Ok(())
+        }
+        RawLayout::Root(root) => {
+            let root_key =
+                hex::decode(root.root_key.trim_start_matches("0x")).map_err(|_| CliError::InvalidMetadata)?;
+            out.push(StorageField {
+                name: prefix,
+                layout: FieldLayout::Mapping { root_key },
+            });
+            Ok(())
+        }
+        RawLayout::Unsupported => Ok(()),
+    }
+}
+
+fn encode_value(spec: &ArgSpec, value: &TranscoderValue, out: &mut Vec<u8>) -> Result<(), CliError> {
+    match (spec.kind, value) {
+        (ArgKind::AccountId, TranscoderValue::AccountId(id)) => out.extend_from_slice(id.as_ref()),
+        (ArgKind::U128, TranscoderValue::U128(v)) => v.encode_to(out),
+        (ArgKind::U64, TranscoderValue::U64(v)) => v.encode_to(out),
+        (ArgKind::U32, TranscoderValue::U32(v)) => v.encode_to(out),
+        (ArgKind::Bool, TranscoderValue::Bool(v)) => v.encode_to(out),
+        (ArgKind::String, TranscoderValue::String(v)) => v.encode_to(out),
+        (ArgKind::Bytes, TranscoderValue::Bytes(v)) => v.encode_to(out),
+        _ => return Err(CliError::UnsupportedArgType(spec.label.clone())),
+    }
+    Ok(())
+}
+
+/// Flag bit set on an `ExecReturnValue` when the contract called
+/// `ink::env::return_value` with `Flags::REVERT`, i.e. the call explicitly
+/// reverted rather than completing normally. A trap does not set this flag;
+/// it comes back as an error result instead.
+const REVERT_FLAG: u32 = 1;
+
+/// A single decoded event field, as produced by [`Transcoder::decode_event`].
+#[derive(Debug, Clone)]
+pub struct DecodedField {
+    pub label: String,
+    pub value: TranscoderValue,
+}
+
+impl Transcoder {
+    /// Returns `true` if the raw flags on a call's `ExecReturnValue`
+    /// indicate the call reverted, as opposed to completing successfully.
+    pub fn is_reverted(flags: u32) -> bool {
+        flags & REVERT_FLAG != 0
+    }
+
+    /// Produces a human-readable revert reason from a reverted call's
+    /// return data, in place of printing raw hex. ink! contracts that
+    /// trap via `LangError` encode a single-byte discriminant; anything
+    /// else falls back to a SCALE-decoded `String` and finally to hex.
+    pub fn decode_revert_reason(data: &[u8]) -> String {
+        if data.len() == 1 {
+            return match data[0] {
+                1 => "CouldNotReadInput".to_string(),
+                other => format!("LangError(discriminant={})", other),
+            };
+        }
+
+        if let Ok(message) = String::decode(&mut &data[..]) {
+            return message;
+        }
+
+        format!("0x{}", hex::encode(data))
+    }
+
+    /// Attempts to decode a `ContractEmitted` payload against every event
+    /// in the metadata's event table, returning the first event whose
+    /// fields fully consume the payload. The table is a `HashMap`, so events
+    /// are tried in no particular order; when more than one event's fields
+    /// fit the payload, which of them is returned is not defined. This
+    /// replaces printing the raw event bytes as hex.
+    pub fn decode_event(&self, data: &[u8]) -> Option<(String, Vec<DecodedField>)> {
+        for event in self.events.values() {
+            if let Some(fields) = decode_event_fields(event, data) {
+                return Some((event.label.clone(), fields));
+            }
+        }
+        None
+    }
+
+    /// Renders a decoded event as `Label { field: value, ... }`.
+    pub fn format_event(label: &str, fields: &[DecodedField]) -> String {
+        let rendered = fields
+            .iter()
+            .map(|f| format!("{}: {}", f.label, format_value(&f.value)))
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!("{} {{ {} }}", label, rendered)
+    }
+}
+
+fn decode_event_fields(event: &EventSpec, data: &[u8]) -> Option<Vec<DecodedField>> {
+    let mut cursor = data;
+    let mut fields = Vec::with_capacity(event.args.len());
+
+    for arg in &event.args {
+        let value = match arg.kind {
+            ArgKind::AccountId => {
+                if cursor.len() < 32 {
+                    return None;
+                }
+                let mut bytes = [0u8; 32];
+                bytes.copy_from_slice(&cursor[..32]);
+                cursor = &cursor[32..];
+                TranscoderValue::AccountId(AccountId::from(bytes))
+            }
+            ArgKind::U128 => decode_scalar::<u128>(&mut cursor).map(TranscoderValue::U128)?,
+            ArgKind::U64 => decode_scalar::<u64>(&mut cursor).map(TranscoderValue::U64)?,
+            ArgKind::U32 => decode_scalar::<u32>(&mut cursor).map(TranscoderValue::U32)?,
+            ArgKind::Bool => decode_scalar::<bool>(&mut cursor).map(TranscoderValue::Bool)?,
+            ArgKind::String => decode_scalar::<String>(&mut cursor).map(TranscoderValue::String)?,
+            ArgKind::Bytes => decode_scalar::<Vec<u8>>(&mut cursor).map(TranscoderValue::Bytes)?,
+        };
+        fields.push(DecodedField {
+            label: arg.label.clone(),
+            value,
+        });
+    }
+
+    if cursor.is_empty() {
+        Some(fields)
+    } else {
+        None
+    }
+}
+
+fn decode_scalar<T: codec::Decode>(cursor: &mut &[u8]) -> Option<T> {
+    T::decode(cursor).ok()
+}
+
+fn format_value(value: &TranscoderValue) -> String {
+    match value {
+        TranscoderValue::AccountId(id) => format!("0x{}", hex::encode(id.as_ref())),
+        TranscoderValue::U128(v) => v.to_string(),
+        TranscoderValue::U64(v) => v.to_string(),
+        TranscoderValue::U32(v) => v.to_string(),
+        TranscoderValue::Bool(v) => v.to_string(),
+        TranscoderValue::String(v) => v.clone(),
+        TranscoderValue::Bytes(v) => format!("0x{}", hex::encode(v)),
+    }
+}
+
+/// Computes the ink! 4-byte message selector as the first 4 bytes of the
+/// BLAKE2b-256 hash of the message label, for contracts deployed without
+/// a metadata file to fall back to convention.
+pub fn selector_from_label(label: &str) -> [u8; 4] {
+    let hash = blake2_256(label.as_bytes());
+    let mut selector = [0u8; 4];
+    selector.copy_from_slice(&hash[0..4]);
+    selector
+}
+
+trait EncodeTo {
+    fn encode_to(&self, out: &mut Vec<u8>);
+}
+
+impl EncodeTo for u128 {
+    fn encode_to(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.encode());
+    }
+}
+
+impl EncodeTo for u64 {
+    fn encode_to(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.encode());
+    }
+}
+
+impl EncodeTo for u32 {
+    fn encode_to(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.encode());
+    }
+}
+
+impl EncodeTo for bool {
+    fn encode_to(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.encode());
+    }
+}
+
+impl EncodeTo for String {
+    fn encode_to(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.encode());
+    }
+}
+
+impl EncodeTo for Vec<u8> {
+    fn encode_to(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.encode());
+    }
+}
@@ -0,0 +1,193 @@
+use subxt::tx::{PairSigner, TxPayload};
+use subxt::OnlineClient;
+use sp_core::sr25519;
+
+use crate::client::{self, NonceManager};
+use crate::storage_sled::{AuditEntry, PriceAction, TypedStorage};
+
+/// One layer of the transaction-submission stack: either submits `call`
+/// itself or forwards to an inner layer that does, so concerns like
+/// slippage checks, retries, nonce/tip assignment, and audit logging each
+/// live in their own layer instead of being tangled together in one
+/// function. Layers compose by construction (`Outer::new(Inner::new(...))`)
+/// rather than as trait objects, since each layer is generic over the
+/// concrete `Call` being submitted.
+pub trait TxPipeline<T: subxt::Config> {
+    async fn send<Call>(
+        &self,
+        api: &OnlineClient<T>,
+        signer: &PairSigner<T, sr25519::Pair>,
+        call: &Call,
+    ) -> Result<T::Hash, Box<dyn std::error::Error>>
+    where
+        Call: TxPayload + Send + Sync;
+}
+
+/// Refuses to submit when `within_bounds` is false, i.e. the price has
+/// already moved past the listener's acceptable level by the time its
+/// turn to execute comes up. Computed once by the caller (which knows the
+/// listener's bound) and carried here rather than recomputed, since this
+/// layer has no opinion on what "in bounds" means for a given action.
+pub struct SlippageGuard<Inner> {
+    inner: Inner,
+    within_bounds: bool,
+}
+
+impl<Inner> SlippageGuard<Inner> {
+    pub fn new(inner: Inner, within_bounds: bool) -> Self {
+        Self { inner, within_bounds }
+    }
+}
+
+impl<T: subxt::Config, Inner: TxPipeline<T> + Sync> TxPipeline<T> for SlippageGuard<Inner> {
+    async fn send<Call>(
+        &self,
+        api: &OnlineClient<T>,
+        signer: &PairSigner<T, sr25519::Pair>,
+        call: &Call,
+    ) -> Result<T::Hash, Box<dyn std::error::Error>>
+    where
+        Call: TxPayload + Send + Sync,
+    {
+        if !self.within_bounds {
+            return Err("slippage guard: price moved outside the listener's bound before submission".into());
+        }
+        self.inner.send(api, signer, call).await
+    }
+}
+
+/// Retries the inner layer up to `max_attempts` times (at least once),
+/// returning the last error if every attempt fails. Useful underneath
+/// flaky RPC endpoints or transient nonce races that the nonce manager's
+/// own single retry didn't absorb.
+pub struct RetryLayer<Inner> {
+    inner: Inner,
+    max_attempts: u8,
+}
+
+impl<Inner> RetryLayer<Inner> {
+    pub fn new(inner: Inner, max_attempts: u8) -> Self {
+        Self { inner, max_attempts: max_attempts.max(1) }
+    }
+}
+
+impl<T: subxt::Config, Inner: TxPipeline<T> + Sync> TxPipeline<T> for RetryLayer<Inner> {
+    async fn send<Call>(
+        &self,
+        api: &OnlineClient<T>,
+        signer: &PairSigner<T, sr25519::Pair>,
+        call: &Call,
+    ) -> Result<T::Hash, Box<dyn std::error::Error>>
+    where
+        Call: TxPayload + Send + Sync,
+    {
+        let mut last_err = None;
+        for _ in 0..self.max_attempts {
+            match self.inner.send(api, signer, call).await {
+                Ok(hash) => return Ok(hash),
+                Err(e) => last_err = Some(e),
+            }
+        }
+        Err(last_err.expect("max_attempts is at least 1, so the loop runs and sets last_err on failure"))
+    }
+}
+
+/// Terminal layer: estimates a tip via `payment_queryInfo`, reserves the
+/// next nonce from `nonces`, signs, and submits, waiting for finalization.
+/// This is where the actual extrinsic leaves the process, so it's also
+/// where the nonce manager (shared across every listener so bursts of
+/// automatic fills get sequential nonces) and fee estimation naturally
+/// sit, rather than being split across separate layers that would each
+/// need to renegotiate the nonce/tip to agree on one extrinsic.
+pub struct FeeEstimatingSubmitter<'a> {
+    nonces: &'a NonceManager,
+    /// Added on top of `payment_queryInfo`'s reported partial fee (or used
+    /// alone if the query fails) to bid above the bare minimum for faster
+    /// inclusion.
+    tip_bump: u128,
+}
+
+impl<'a> FeeEstimatingSubmitter<'a> {
+    pub fn new(nonces: &'a NonceManager, tip_bump: u128) -> Self {
+        Self { nonces, tip_bump }
+    }
+}
+
+impl<'a, T: subxt::Config> TxPipeline<T> for FeeEstimatingSubmitter<'a>
+where
+    T::AccountId: Clone + From<[u8; 32]>,
+{
+    async fn send<Call>(
+        &self,
+        api: &OnlineClient<T>,
+        signer: &PairSigner<T, sr25519::Pair>,
+        call: &Call,
+    ) -> Result<T::Hash, Box<dyn std::error::Error>>
+    where
+        Call: TxPayload + Send + Sync,
+    {
+        let tip = estimate_tip(api, call, self.tip_bump).await.unwrap_or(self.tip_bump);
+        let progress = client::submit_with_nonce_and_tip(api, signer, self.nonces, call, tip).await?;
+        let in_block = progress.wait_for_finalized_success().await?;
+        Ok(in_block.extrinsic_hash())
+    }
+}
+
+/// Best-effort `payment_queryInfo` lookup for `call`'s partial fee, on top
+/// of which `bump` is added. Falls back to the caller's own handling
+/// (currently: just use `bump`) on any RPC or encoding failure, since a
+/// failed fee *estimate* shouldn't block submitting the transaction.
+async fn estimate_tip<T: subxt::Config, Call: TxPayload>(
+    api: &OnlineClient<T>,
+    call: &Call,
+    bump: u128,
+) -> Result<u128, Box<dyn std::error::Error>> {
+    let call_data = call.encode_call_data(&api.metadata())?;
+    let info = api.rpc().payment_query_info(&call_data, None).await?;
+    Ok(info.partial_fee.saturating_add(bump))
+}
+
+/// Records every executed `PriceAction` to local storage once the inner
+/// layers have successfully submitted it, so a listener's fill history
+/// survives process restarts and can be audited independently of chain
+/// explorers.
+pub struct AuditLogger<'a, Inner, S> {
+    inner: Inner,
+    storage: &'a S,
+    token_id: u128,
+    action: PriceAction,
+    price: u128,
+}
+
+impl<'a, Inner, S> AuditLogger<'a, Inner, S> {
+    pub fn new(inner: Inner, storage: &'a S, token_id: u128, action: PriceAction, price: u128) -> Self {
+        Self { inner, storage, token_id, action, price }
+    }
+}
+
+impl<'a, T, Inner, S> TxPipeline<T> for AuditLogger<'a, Inner, S>
+where
+    T: subxt::Config,
+    T::Hash: Into<sp_core::H256>,
+    Inner: TxPipeline<T> + Sync,
+    S: TypedStorage,
+{
+    async fn send<Call>(
+        &self,
+        api: &OnlineClient<T>,
+        signer: &PairSigner<T, sr25519::Pair>,
+        call: &Call,
+    ) -> Result<T::Hash, Box<dyn std::error::Error>>
+    where
+        Call: TxPayload + Send + Sync,
+    {
+        let hash = self.inner.send(api, signer, call).await?;
+        self.storage.record_audit_entry(&AuditEntry {
+            token_id: self.token_id,
+            action: self.action.clone(),
+            price: self.price,
+            tx_hash: hash.into(),
+        })?;
+        Ok(hash)
+    }
+}